@@ -0,0 +1,514 @@
+// Package run_checks
+// File: src/config.rs
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+/// File name looked up from the current directory up to the repository root.
+pub const CONFIG_FILE: &str = "run_checks.toml";
+
+/// Project configuration. Every field has a default, so an empty or missing
+/// `run_checks.toml` behaves exactly like the built-in settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub tools: ToolsConfig,
+    pub scan: ScanConfig,
+    pub files: FilesConfig,
+    pub tree: TreeConfig,
+    pub matrix: MatrixConfig,
+}
+
+/// `[tools]`: which built-in cargo tools run and with what arguments.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    /// Built-in tools to run, in report order: `rustfmt`, `clippy`, `cargo check`, `cargo test`.
+    pub run: Vec<String>,
+    /// Cargo arguments for `cargo clippy` (before `--`).
+    pub clippy_args: Vec<String>,
+    /// Lint arguments passed to clippy after `--`.
+    pub clippy_lints: Vec<String>,
+    /// Extra arguments for `cargo check`.
+    pub check_args: Vec<String>,
+    /// Extra arguments for `cargo test`.
+    pub test_args: Vec<String>,
+    /// User-defined commands, run and reported after the built-ins.
+    pub custom: Vec<CustomTool>,
+    /// Default timeout for every tool, in seconds. Unset means no limit.
+    pub timeout_secs: Option<u64>,
+    /// Per-tool timeouts by row name (built-in or custom), in seconds.
+    pub timeouts: BTreeMap<String, u64>,
+    /// Deadline for the whole run, matrices included, in seconds. Once it
+    /// passes, running tools are killed and the rest are not started.
+    pub run_timeout_secs: Option<u64>,
+    /// Maximum number of tools running at once. Unset means no limit.
+    pub jobs: Option<usize>,
+    /// What to do once a tool fails.
+    pub on_failure: FailurePolicy,
+    /// Built-in tools whose failure is reported as a warning only.
+    pub allow_failure: Vec<String>,
+    /// In a multi-member workspace, pass `--workspace` to clippy, check and
+    /// test (plus `--no-fail-fast` to test) and report results per member.
+    pub workspace: bool,
+}
+
+/// `[tools].on_failure`: how the pipeline reacts to a failed tool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailurePolicy {
+    /// Run every tool regardless of earlier failures.
+    #[default]
+    KeepGoing,
+    /// Skip tools whose dependencies did not succeed; run the rest.
+    SkipDependents,
+    /// Start nothing new and cancel running tools on the first failure.
+    FailFast,
+}
+
+/// One `[[tools.custom]]` entry.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomTool {
+    /// Row name in the report.
+    pub name: String,
+    /// Program and arguments; not run through a shell.
+    pub command: Vec<String>,
+    /// Working directory, relative to where run_checks was started.
+    pub cwd: Option<PathBuf>,
+    /// Extra environment variables.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Exit codes that count as success.
+    #[serde(default = "default_ok_exit_codes")]
+    pub ok_exit_codes: Vec<i32>,
+    /// Kill the command and fail the row after this many seconds.
+    pub timeout_secs: Option<u64>,
+    /// Rows (built-in or custom) that must finish before this one starts.
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Take the build-directory lock shared with clippy, check and test.
+    #[serde(default)]
+    pub build_lock: bool,
+    /// Report a failure as a warning without failing the run.
+    #[serde(default)]
+    pub allow_failure: bool,
+}
+
+fn default_ok_exit_codes() -> Vec<i32> {
+    vec![0]
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            run: strings(&["rustfmt", "clippy", "cargo check", "cargo test"]),
+            clippy_args: strings(&["--all-targets", "--all-features"]),
+            clippy_lints: strings(&["-D", "warnings"]),
+            check_args: Vec::new(),
+            test_args: Vec::new(),
+            custom: Vec::new(),
+            timeout_secs: None,
+            timeouts: BTreeMap::new(),
+            run_timeout_secs: None,
+            jobs: None,
+            on_failure: FailurePolicy::KeepGoing,
+            allow_failure: Vec::new(),
+            workspace: true,
+        }
+    }
+}
+
+impl ToolsConfig {
+    /// Effective timeout for a row: the entry's own setting, then
+    /// `[tools.timeouts]`, then `[tools].timeout_secs`.
+    pub fn timeout_for(&self, name: &str, own: Option<u64>) -> Option<u64> {
+        own.or_else(|| self.timeouts.get(name).copied()).or(self.timeout_secs)
+    }
+
+    /// Name of a custom tool that can reach itself through `after`, if any.
+    /// Built-ins never depend on custom tools, so only custom edges matter.
+    fn dependency_cycle(&self) -> Option<&str> {
+        fn reaches<'a>(
+            cfg: &'a ToolsConfig,
+            from: &'a str,
+            target: &str,
+            seen: &mut Vec<&'a str>,
+        ) -> bool {
+            let Some(c) = cfg.custom.iter().find(|c| c.name == from) else {
+                return false;
+            };
+            for dep in &c.after {
+                if dep == target {
+                    return true;
+                }
+                if !seen.contains(&dep.as_str()) {
+                    seen.push(dep);
+                    if reaches(cfg, dep, target, seen) {
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+        self.custom
+            .iter()
+            .find(|c| reaches(self, &c.name, &c.name, &mut Vec::new()))
+            .map(|c| c.name.as_str())
+    }
+}
+
+/// `[scan]`: patterns used by the Extra scans row.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    /// Line rules as rule id -> regex. Entries here replace or add to the
+    /// built-in detector catalog.
+    pub patterns: BTreeMap<String, String>,
+    /// Rule ids to drop entirely (built-in or configured).
+    pub disabled_rules: Vec<String>,
+    /// Personal-data regex, applied only under `pii_dirs`.
+    pub pii_pattern: String,
+    /// Directory names whose files get the PII scan.
+    pub pii_dirs: Vec<String>,
+    /// Globs for credential-bearing files. Globs without `/` match the file
+    /// name; others match the path relative to the project root.
+    pub leak_files: Vec<String>,
+    /// Reuse findings for unchanged files from `target/run_checks/scan-cache.json`.
+    pub cache: bool,
+    /// Accepted findings written by `run_checks baseline write`; only findings
+    /// not recorded here are reported as found.
+    pub baseline: String,
+    /// Known-safe matches by rule id; `"*"` applies to every rule.
+    pub allow: BTreeMap<String, AllowList>,
+    /// Thresholds for the `high-entropy-string` rule.
+    pub entropy: EntropyConfig,
+}
+
+/// `[scan.entropy]`: quoted or assigned tokens this random are reported as
+/// likely secrets. Entropy is Shannon bits per character; since a token of
+/// `n` characters cannot exceed `log2(n)`, each threshold is capped at
+/// `0.85 * log2(n)` (n capped at 16 for hex, 64 otherwise), so tokens as
+/// short as `min_length` can still be reported.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EntropyConfig {
+    /// Shorter tokens are ignored.
+    pub min_length: usize,
+    /// Threshold for tokens made only of hex digits (at most 4.0).
+    pub hex_threshold: f64,
+    /// Threshold for other base64/base64url tokens (at most 6.0).
+    pub base64_threshold: f64,
+}
+
+impl Default for EntropyConfig {
+    fn default() -> Self {
+        Self { min_length: 20, hex_threshold: 3.0, base64_threshold: 4.5 }
+    }
+}
+
+/// `[scan.allow.<rule>]`: matches never reported for that rule.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AllowList {
+    /// Matched text ignored exactly, e.g. a username such as `git`.
+    pub values: Vec<String>,
+    /// Matched text ignored when one of these regexes matches it.
+    pub patterns: Vec<String>,
+    /// Globs over paths relative to the project root, e.g. `tests/fixtures/**`.
+    pub paths: Vec<String>,
+}
+
+impl AllowList {
+    pub fn path_globs(&self) -> Result<GlobSet, String> {
+        let mut set = GlobSetBuilder::new();
+        for pat in &self.paths {
+            set.add(Glob::new(pat).map_err(|e| format!("[scan.allow] paths: {e}"))?);
+        }
+        set.build().map_err(|e| format!("[scan.allow] paths: {e}"))
+    }
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            patterns: BTreeMap::new(),
+            disabled_rules: Vec::new(),
+            pii_pattern: r"(?i)(email|@example|phone|address|SIN|SSN|passport|license)".to_string(),
+            pii_dirs: strings(&["docs", "examples", "tests"]),
+            leak_files: strings(&[
+                ".env*",
+                ".envrc",
+                "kubeconfig",
+                "id_rsa*",
+                "id_ed25519*",
+                "*.pem",
+                "*.p12",
+                "*.crt",
+                "*.key",
+                "**/.kube/config",
+                ".npmrc",
+                ".pypirc",
+                ".netrc",
+                ".git-credentials",
+                ".aws",
+            ]),
+            cache: true,
+            baseline: ".run_checks/baseline.json".to_string(),
+            allow: BTreeMap::new(),
+            entropy: EntropyConfig::default(),
+        }
+    }
+}
+
+/// `[files]`: which files `files`, `tree` and the scanners look at.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesConfig {
+    /// File extensions treated as project text files.
+    pub extensions: Vec<String>,
+    /// Directory names never descended into.
+    pub skip_dirs: Vec<String>,
+    /// Files larger than this many bytes are not scanned.
+    pub max_scan_bytes: u64,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            extensions: strings(&["rs", "md", "sh", "toml"]),
+            skip_dirs: strings(&[".git", "target", "node_modules"]),
+            max_scan_bytes: 1_000_000,
+        }
+    }
+}
+
+impl FilesConfig {
+    pub fn is_allowed_file(&self, p: &Path) -> bool {
+        p.extension()
+            .and_then(|s| s.to_str())
+            .map(|ext| self.extensions.iter().any(|e| e == ext))
+            .unwrap_or(false)
+    }
+
+    pub fn is_skipped_dir(&self, p: &Path) -> bool {
+        let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
+        self.skip_dirs.iter().any(|d| d == name)
+    }
+
+    /// Walk `root`, skipping configured directories and symlinks.
+    pub fn walk(&self, root: &Path) -> impl Iterator<Item = DirEntry> + '_ {
+        WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| !(self.is_skipped_dir(e.path()) || e.path_is_symlink()))
+            .filter_map(Result::ok)
+    }
+}
+
+/// `[tree]`: defaults for the `tree` subcommand.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TreeConfig {
+    /// Depth used when `--depth` is not given.
+    pub depth: usize,
+    /// Show every file, not just those matching `[files].extensions`.
+    pub all_files: bool,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self { depth: 2, all_files: false }
+    }
+}
+
+/// `[matrix]`: extra runs of the compile steps under other feature sets.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatrixConfig {
+    /// Run the feature matrix on every `checks` (same as `--feature-matrix`).
+    pub features: bool,
+    /// Tools run for each feature set.
+    pub feature_tools: Vec<String>,
+    /// Largest feature combination tried: 1 = each feature alone, 2 = also pairs, ...
+    pub powerset_depth: usize,
+    /// Features never enabled on their own or in combinations.
+    pub skip_features: Vec<String>,
+    /// Toolchains to run `toolchain_tools` under (`cargo +<name>`); `msrv`
+    /// means the `rust-version` from Cargo.toml.
+    pub toolchains: Vec<String>,
+    /// Tools run for each toolchain.
+    pub toolchain_tools: Vec<String>,
+}
+
+impl Default for MatrixConfig {
+    fn default() -> Self {
+        Self {
+            features: false,
+            feature_tools: strings(&["cargo check", "clippy"]),
+            powerset_depth: 1,
+            skip_features: Vec::new(),
+            toolchains: Vec::new(),
+            toolchain_tools: strings(&["cargo check", "cargo test"]),
+        }
+    }
+}
+
+impl Config {
+    /// Load `explicit` if given, else the nearest `run_checks.toml` found by
+    /// walking up from the current directory (stopping at the repository root).
+    /// No file found means built-in defaults.
+    pub fn load(explicit: Option<&Path>) -> Result<(Config, Option<PathBuf>), String> {
+        let path = match explicit {
+            Some(p) => Some(p.to_path_buf()),
+            None => discover(),
+        };
+        let Some(path) = path else {
+            return Ok((Config::default(), None));
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let cfg: Config =
+            toml::from_str(&text).map_err(|e| format!("invalid {}: {e}", path.display()))?;
+        cfg.validate().map_err(|e| format!("invalid {}: {e}", path.display()))?;
+        Ok((cfg, Some(path)))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        const BUILTINS: &[&str] = &["rustfmt", "clippy", "cargo check", "cargo test"];
+        for name in &self.tools.run {
+            if !BUILTINS.contains(&name.as_str()) {
+                return Err(format!("[tools].run: unknown tool `{name}`"));
+            }
+        }
+        for name in &self.tools.allow_failure {
+            if !BUILTINS.contains(&name.as_str()) {
+                return Err(format!(
+                    "[tools].allow_failure: unknown tool `{name}` (use `allow_failure = true` on custom tools)"
+                ));
+            }
+        }
+        let mut seen: Vec<&str> = BUILTINS.to_vec();
+        for c in &self.tools.custom {
+            if c.name.trim().is_empty() {
+                return Err("[[tools.custom]]: `name` must not be empty".to_string());
+            }
+            if seen.contains(&c.name.as_str()) {
+                return Err(format!("[[tools.custom]]: duplicate tool name `{}`", c.name));
+            }
+            seen.push(&c.name);
+            if c.command.is_empty() {
+                return Err(format!("[[tools.custom]] `{}`: `command` must not be empty", c.name));
+            }
+        }
+        for name in self.tools.timeouts.keys() {
+            if !seen.contains(&name.as_str()) {
+                return Err(format!("[tools.timeouts]: unknown tool `{name}`"));
+            }
+        }
+        for c in &self.tools.custom {
+            if let Some(dep) = c.after.iter().find(|d| !seen.contains(&d.as_str())) {
+                return Err(format!(
+                    "[[tools.custom]] `{}`: unknown tool `{dep}` in `after`",
+                    c.name
+                ));
+            }
+        }
+        if let Some(name) = self.tools.dependency_cycle() {
+            return Err(format!("[[tools.custom]] `{name}`: `after` forms a cycle"));
+        }
+        if self.tools.jobs == Some(0) {
+            return Err("[tools].jobs must be at least 1".to_string());
+        }
+        for name in &self.matrix.feature_tools {
+            if !BUILTINS[1..].contains(&name.as_str()) {
+                return Err(format!("[matrix].feature_tools: unsupported tool `{name}`"));
+            }
+        }
+        for name in &self.matrix.toolchain_tools {
+            if !BUILTINS[1..].contains(&name.as_str()) {
+                return Err(format!("[matrix].toolchain_tools: unsupported tool `{name}`"));
+            }
+        }
+        if self.matrix.powerset_depth == 0 {
+            return Err("[matrix].powerset_depth must be at least 1".to_string());
+        }
+        for (id, re) in &self.scan.patterns {
+            regex::Regex::new(re).map_err(|e| format!("[scan].patterns.{id}: {e}"))?;
+        }
+        regex::Regex::new(&self.scan.pii_pattern)
+            .map_err(|e| format!("[scan].pii_pattern: {e}"))?;
+        self.scan.leak_file_globs()?;
+        if self.scan.entropy.min_length == 0 {
+            return Err("[scan.entropy].min_length must be at least 1".to_string());
+        }
+        for (rule, allow) in &self.scan.allow {
+            let known = rule == "*"
+                || crate::run_checks::RULES.iter().any(|r| r.id == rule)
+                || self.scan.patterns.contains_key(rule);
+            if !known {
+                return Err(format!("[scan.allow]: unknown rule `{rule}`"));
+            }
+            for re in &allow.patterns {
+                regex::Regex::new(re).map_err(|e| format!("[scan.allow.{rule}] patterns: {e}"))?;
+            }
+            allow.path_globs()?;
+        }
+        Ok(())
+    }
+}
+
+impl ScanConfig {
+    /// Compile `leak_files` into (name globs, path globs).
+    pub fn leak_file_globs(&self) -> Result<(GlobSet, GlobSet), String> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        for pat in &self.leak_files {
+            let glob = Glob::new(pat).map_err(|e| format!("[scan].leak_files: {e}"))?;
+            if pat.contains('/') {
+                paths.add(glob);
+            } else {
+                names.add(glob);
+            }
+        }
+        let names = names.build().map_err(|e| format!("[scan].leak_files: {e}"))?;
+        let paths = paths.build().map_err(|e| format!("[scan].leak_files: {e}"))?;
+        Ok((names, paths))
+    }
+}
+
+fn discover() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    for dir in cwd.ancestors() {
+        let candidate = dir.join(CONFIG_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(text: &str) -> Result<(), String> {
+        toml::from_str::<Config>(text).map_err(|e| e.to_string())?.validate()
+    }
+
+    #[test]
+    fn invalid_leak_glob_is_rejected_at_load() {
+        let err = validate("[scan]\nleak_files = [\"secrets/[\"]\n").unwrap_err();
+        assert!(err.starts_with("[scan].leak_files:"), "{err}");
+        assert!(validate("[scan]\nleak_files = [\"*.pem\", \"conf/*.key\"]\n").is_ok());
+    }
+}
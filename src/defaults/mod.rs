@@ -3,6 +3,7 @@
 
 use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, Table};
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use std::fmt::Write as _; // for write! to String
 use std::fs;
 use std::io::Write;
@@ -14,38 +15,44 @@ use std::os::unix::fs::PermissionsExt;
 mod templates;
 use templates::{
     DEFAULT_CARGO_CONFIG, DEFAULT_CHANGELOG, DEFAULT_CONTRIBUTING, DEFAULT_EDITORCONFIG,
-    DEFAULT_GITIGNORE, DEFAULT_LICENSE, DEFAULT_MAIN_RS, DEFAULT_README, DEFAULT_RUSTFMT,
-    DEFAULT_SCRIPT,
+    DEFAULT_GITIGNORE, DEFAULT_LICENSE, DEFAULT_MAIN_RS, DEFAULT_README, DEFAULT_RUN_CHECKS_CONFIG,
+    DEFAULT_RUSTFMT, DEFAULT_SCRIPT,
 };
 
+/// What `create-defaults` did (or found) for one path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Exists,
+    Created,
+    Error,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DefaultAction {
+    pub path: String,
+    pub action: Action,
+    pub details: String,
+}
+
 /// Ensure default files and folders exist; create them if missing.
 /// Also creates `src/main.rs` from template if absent.
-pub fn ensure_defaults() -> String {
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL).set_header(vec!["Path", "Action", "Details"]);
+pub fn ensure_defaults() -> Vec<DefaultAction> {
+    let mut actions = Vec::new();
+    let mut record = |path: &str, action: Action, details: String| {
+        actions.push(DefaultAction { path: path.to_string(), action, details });
+    };
 
     // Minimal required directory: .cargo
     {
         let d = ".cargo";
         let p = Path::new(d);
         if p.exists() {
-            table.add_row(vec![
-                Cell::new(d),
-                Cell::new("exists").add_attribute(Attribute::Bold).fg(Color::Green),
-                Cell::new("dir present"),
-            ]);
+            record(d, Action::Exists, "dir present".to_string());
         } else if let Err(e) = fs::create_dir_all(p) {
-            table.add_row(vec![
-                Cell::new(d),
-                Cell::new("error").add_attribute(Attribute::Bold).fg(Color::Red),
-                Cell::new(e.to_string()),
-            ]);
+            record(d, Action::Error, e.to_string());
         } else {
-            table.add_row(vec![
-                Cell::new(d),
-                Cell::new("created").add_attribute(Attribute::Bold).fg(Color::Yellow),
-                Cell::new("directory"),
-            ]);
+            record(d, Action::Created, "directory".to_string());
         }
     }
 
@@ -60,15 +67,12 @@ pub fn ensure_defaults() -> String {
         ("CONTRIBUTING.md", DEFAULT_CONTRIBUTING, None::<fn()>),
         (".editorconfig", DEFAULT_EDITORCONFIG, None::<fn()>),
         (".cargo/config.toml", DEFAULT_CARGO_CONFIG, None::<fn()>),
+        ("run_checks.toml", DEFAULT_RUN_CHECKS_CONFIG, None::<fn()>),
         ("src/main.rs", DEFAULT_MAIN_RS, None::<fn()>),
     ] {
         let p = Path::new(path);
         if p.exists() {
-            table.add_row(vec![
-                Cell::new(path),
-                Cell::new("exists").add_attribute(Attribute::Bold).fg(Color::Green),
-                Cell::new("no changes"),
-            ]);
+            record(path, Action::Exists, "no changes".to_string());
             continue;
         }
 
@@ -77,22 +81,30 @@ pub fn ensure_defaults() -> String {
                 if let Some(f) = post {
                     f();
                 }
-                table.add_row(vec![
-                    Cell::new(path),
-                    Cell::new("created").add_attribute(Attribute::Bold).fg(Color::Yellow),
-                    Cell::new("default template written"),
-                ]);
-            }
-            Err(e) => {
-                table.add_row(vec![
-                    Cell::new(path),
-                    Cell::new("error").add_attribute(Attribute::Bold).fg(Color::Red),
-                    Cell::new(e.to_string()),
-                ]);
+                record(path, Action::Created, "default template written".to_string());
             }
+            Err(e) => record(path, Action::Error, e.to_string()),
         }
     }
 
+    actions
+}
+
+/// Render `create-defaults` results as a titled table.
+pub fn render_defaults(actions: &[DefaultAction]) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header(vec!["Path", "Action", "Details"]);
+    for a in actions {
+        let action = match a.action {
+            Action::Exists => Cell::new("exists").add_attribute(Attribute::Bold).fg(Color::Green),
+            Action::Created => {
+                Cell::new("created").add_attribute(Attribute::Bold).fg(Color::Yellow)
+            }
+            Action::Error => Cell::new("error").add_attribute(Attribute::Bold).fg(Color::Red),
+        };
+        table.add_row(vec![Cell::new(&a.path), action, Cell::new(&a.details)]);
+    }
+
     let mut out = String::new();
     let _ = writeln!(out, "{}", "Create Defaults? results:".cyan());
     let _ = writeln!(out, "{table}");
@@ -11,6 +11,7 @@ pub const DEFAULT_CHANGELOG: &str = include_str!("./templates/CHANGELOG.md.templ
 pub const DEFAULT_CONTRIBUTING: &str = include_str!("./templates/CONTRIBUTING.md.template");
 pub const DEFAULT_EDITORCONFIG: &str = include_str!("./templates/.editorconfig.template");
 pub const DEFAULT_CARGO_CONFIG: &str = include_str!("./templates/config.toml.template");
+pub const DEFAULT_RUN_CHECKS_CONFIG: &str = include_str!("./templates/run_checks.toml.template");
 
 // new: starter for src/main.rs
 pub const DEFAULT_MAIN_RS: &str = include_str!("./templates/main.rs.template");
@@ -1,60 +1,64 @@
 // Package run_checks
 // File: src/display_all.rs
 
+use crate::config::Config;
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use std::{fs, path::Path};
-use walkdir::WalkDir;
 
-const ALLOWED_EXTS: &[&str] = &["rs", "md", "sh", "toml"];
-
-fn is_allowed_file(p: &Path) -> bool {
-    p.extension().and_then(|s| s.to_str()).map(|ext| ALLOWED_EXTS.contains(&ext)).unwrap_or(false)
+/// One project text file and its contents.
+#[derive(Clone, Debug, Serialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub contents: String,
 }
 
-/// Build a single String that contains all allowed text files in the repo,
-/// skipping `target/`, `.git/`, `node_modules/`, and symlinks.
-pub fn collect_all_rs() -> String {
-    let mut out = String::new();
-    out.push_str(&format!("{}\n", "Displaying contents of .rs/.md/.sh/.toml files:".cyan()));
-
+/// Collect all allowed text files in the repo, sorted by path,
+/// skipping `[files].skip_dirs` (`target/`, `.git/`, `node_modules/` by default) and symlinks.
+pub fn collect_files(cfg: &Config) -> Vec<FileEntry> {
     // Gather & sort paths for stable output.
     let mut paths = Vec::new();
-    for entry in WalkDir::new(".")
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name == "node_modules" || p.is_symlink())
-        })
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
+    for entry in cfg.files.walk(Path::new(".")).filter(|e| e.file_type().is_file()) {
         let p = entry.path().to_path_buf();
-        if is_allowed_file(&p) {
+        if cfg.files.is_allowed_file(&p) {
             paths.push(p);
         }
     }
     paths.sort();
 
+    let mut files = Vec::new();
     for path in paths {
         match fs::read_to_string(&path) {
-            Ok(contents) => {
-                out.push_str(&format!(
-                    "{}\n{}\n{}\n",
-                    "========================================".green(),
-                    path.display(),
-                    "========================================".green()
-                ));
-                out.push_str(&contents);
-                if !contents.ends_with('\n') {
-                    out.push('\n');
-                }
-            }
+            Ok(contents) => files.push(FileEntry { path: path.display().to_string(), contents }),
             Err(_) => {
                 // Non-UTF8 or unreadable: skip (avoids binaries).
             }
         }
     }
+    files
+}
+
+/// Build a single String that contains every collected file under a banner.
+pub fn render_files(cfg: &Config, files: &[FileEntry]) -> String {
+    let exts: Vec<String> = cfg.files.extensions.iter().map(|e| format!(".{e}")).collect();
+    let mut out = String::new();
+    out.push_str(&format!(
+        "{}\n",
+        format!("Displaying contents of {} files:", exts.join("/")).cyan()
+    ));
+
+    for f in files {
+        out.push_str(&format!(
+            "{}\n{}\n{}\n",
+            "========================================".green(),
+            f.path,
+            "========================================".green()
+        ));
+        out.push_str(&f.contents);
+        if !f.contents.ends_with('\n') {
+            out.push('\n');
+        }
+    }
 
     out
 }
@@ -0,0 +1,188 @@
+// Package run_checks
+// File: src/junit.rs
+
+use crate::run_checks::{ChecksReport, ScanStatus, ToolResult, ToolStatus};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Write a JUnit XML report: one `<testsuite>` for the core tools, one for
+/// each matrix (features, toolchains) when present, and one for the privacy/security rows,
+/// each row a `<testcase>`.
+pub fn write_junit(path: &Path, report: &ChecksReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    std::fs::write(path, render_junit(report))
+}
+
+fn render_junit(report: &ChecksReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(out, r#"<testsuites name="run_checks">"#);
+
+    // Core tools, then matrix cells
+    let tools: Vec<&ToolResult> = report.tools.results.iter().collect();
+    push_tool_suite(&mut out, "tools", &tools, report.tools.elapsed_secs);
+    let matrices = [
+        ("feature-matrix", &report.feature_matrix),
+        ("toolchain-matrix", &report.toolchain_matrix),
+    ];
+    for (suite, m) in matrices {
+        let Some(m) = m else {
+            continue;
+        };
+        let cells: Vec<&ToolResult> = m.rows.iter().flat_map(|r| &r.cells).collect();
+        let time = cells.iter().map(|r| r.elapsed_secs).sum();
+        push_tool_suite(&mut out, suite, &cells, time);
+    }
+
+    // Privacy/security rows
+    let rows = &report.privacy;
+    let failures = rows.iter().filter(|r| r.status == ScanStatus::Found).count();
+    let skipped = rows
+        .iter()
+        .filter(|r| matches!(r.status, ScanStatus::Skipped | ScanStatus::NotApplicable))
+        .count();
+    let _ = writeln!(
+        out,
+        r#"  <testsuite name="privacy" tests="{}" failures="{failures}" errors="0" skipped="{skipped}">"#,
+        rows.len()
+    );
+    for row in rows {
+        let _ = write!(
+            out,
+            r#"    <testcase classname="run_checks.privacy" name="{}""#,
+            escape(&format!("{}: {}", row.check, row.value))
+        );
+        match row.status {
+            ScanStatus::NotFound | ScanStatus::Info => out.push_str(" />\n"),
+            ScanStatus::Found => {
+                let _ = writeln!(
+                    out,
+                    r#">
+      <failure message="{}" type="found">{}</failure>
+    </testcase>"#,
+                    escape(&row.details),
+                    escape(&row.locations.join("\n"))
+                );
+            }
+            ScanStatus::Skipped | ScanStatus::NotApplicable => {
+                let _ = writeln!(
+                    out,
+                    r#">
+      <skipped message="{}" />
+    </testcase>"#,
+                    escape(&row.details)
+                );
+            }
+        }
+    }
+    out.push_str("  </testsuite>\n");
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Failure body: the full diagnostics/test failures when parsed, else the details.
+fn tool_failure_body(r: &ToolResult) -> String {
+    let mut body = r.details.clone();
+    if let Some(diags) = &r.diagnostics {
+        for d in diags {
+            let code = d.code.as_deref().map(|c| format!("[{c}]")).unwrap_or_default();
+            let _ = write!(
+                body,
+                "\n{}{code} {}:{}:{}: {}",
+                d.level, d.file, d.line, d.column, d.message
+            );
+        }
+    }
+    if let Some(tests) = &r.tests {
+        for f in &tests.failures {
+            let _ = write!(body, "\nFAILED {} ({}): {}", f.name, f.binary, f.message);
+        }
+    }
+    body
+}
+
+fn push_output(out: &mut String, tag: &str, text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "      <{tag}>{}</{tag}>", escape(text));
+}
+
+/// One `<testsuite>` with a `<testcase>` per tool result.
+fn push_tool_suite(out: &mut String, suite: &str, tools: &[&ToolResult], time: f64) {
+    let failures = tools.iter().filter(|r| !r.ok()).count();
+    let skipped = tools
+        .iter()
+        .filter(|r| matches!(r.status, ToolStatus::Skipped | ToolStatus::Cancelled))
+        .count();
+    let _ = writeln!(
+        out,
+        r#"  <testsuite name="{suite}" tests="{}" failures="{failures}" errors="0" skipped="{skipped}" time="{time:.3}">"#,
+        tools.len()
+    );
+    for r in tools {
+        let _ = write!(
+            out,
+            r#"    <testcase classname="run_checks.{suite}" name="{}" time="{:.3}""#,
+            escape(&r.name),
+            r.elapsed_secs
+        );
+        match r.status {
+            // Allowed failures are warnings: the case passes, output still attached.
+            _ if r.warning() => {
+                out.push_str(">\n");
+            }
+            ToolStatus::Success => {
+                out.push_str(">\n");
+            }
+            ToolStatus::Skipped | ToolStatus::Cancelled => {
+                let _ = writeln!(
+                    out,
+                    r#">
+      <skipped message="{}" />"#,
+                    escape(&r.details)
+                );
+            }
+            ToolStatus::Failed | ToolStatus::TimedOut => {
+                let kind = if r.status == ToolStatus::TimedOut { "timeout" } else { "failed" };
+                let _ = writeln!(
+                    out,
+                    r#">
+      <failure message="{}" type="{kind}">{}</failure>"#,
+                    escape(&first_line(&r.details, kind)),
+                    escape(&tool_failure_body(r))
+                );
+            }
+        }
+        push_output(out, "system-out", &r.stdout);
+        push_output(out, "system-err", &r.stderr);
+        out.push_str("    </testcase>\n");
+    }
+    out.push_str("  </testsuite>\n");
+}
+
+fn first_line(s: &str, fallback: &str) -> String {
+    s.lines().next().filter(|l| !l.is_empty()).unwrap_or(fallback).to_string()
+}
+
+/// Escape XML special characters and drop control characters XML 1.0 forbids.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
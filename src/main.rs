@@ -1,13 +1,19 @@
 // Snippet
 // File: src/main.rs
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
+mod config; // run_checks.toml discovery and settings
 mod defaults; // now a module directory: src/defaults/mod.rs + templates.rs
 mod display_all;
+mod junit; // JUnit XML export of a checks run
+mod report; // versioned JSON output schema
 mod run_checks; // orchestrates core tools + privacy table
+mod sarif; // SARIF export of privacy/secret findings
 mod tree;
 mod util; // clipboard, clear screen, ANSI stripping
+mod watch; // re-run checks on file changes
 
 /// CLI for one-shot checks and project introspection.
 #[derive(Parser)]
@@ -21,6 +27,20 @@ Examples:
   cargo run -- \"Checks plus Extras\"
   cargo run -- create-defaults
   cargo run -- all --depth 3 --clear
+  cargo run -- checks --fmt check
+  cargo run -- checks --format json
+  cargo run -- checks --timeout 600
+  cargo run -- checks --feature-matrix --run-timeout 1800
+  cargo run -- checks --jobs 1
+  cargo run -- checks --fail-fast
+  cargo run -- checks --feature-matrix --only clippy
+  cargo run -- checks --toolchains stable,msrv,nightly
+  cargo run -- watch --skip \"cargo test\"
+  cargo run -- baseline write
+  cargo run -- checks-extras --only clippy,extras
+  cargo run -- checks --skip \"cargo test\"
+  cargo run -- checks-extras --junit target/run_checks/junit.xml
+  cargo run -- checks-extras --sarif target/run_checks/findings.sarif
   ./run_checks all --depth 3 --clear"
 )]
 struct Cli {
@@ -28,18 +48,90 @@ struct Cli {
     #[arg(long)]
     clear: bool,
 
+    /// rustfmt row mode: `check` never rewrites files; `auto` uses `check` under CI
+    /// or when stdout is not a terminal
+    #[arg(long, value_enum, default_value_t = run_checks::FmtMode::Auto, global = true)]
+    fmt: run_checks::FmtMode,
+
+    /// Default per-tool timeout in seconds (overrides `[tools].timeout_secs`)
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Deadline for the whole run in seconds (overrides `[tools].run_timeout_secs`)
+    #[arg(long, global = true, value_name = "SECS")]
+    run_timeout: Option<u64>,
+
+    /// Maximum number of tools running at once (overrides `[tools].jobs`)
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    jobs: Option<u64>,
+
+    /// On the first failure, start no more tools and cancel running ones
+    #[arg(long, global = true, conflicts_with = "keep_going")]
+    fail_fast: bool,
+
+    /// Run every tool even after failures (overrides `[tools].on_failure`)
+    #[arg(long, global = true)]
+    keep_going: bool,
+
+    /// Also run the feature matrix (`[matrix].features`)
+    #[arg(long, global = true)]
+    feature_matrix: bool,
+
+    /// Also run `[matrix].toolchain_tools` under these toolchains, e.g. `stable,msrv,nightly`
+    #[arg(long, global = true, value_delimiter = ',', value_name = "TOOLCHAIN")]
+    toolchains: Option<Vec<String>>,
+
+    /// Run only these tools / scan kinds (comma-separated or repeated), e.g.
+    /// `clippy,extras`. Scan kinds: username, hostname, ip-address, extras
+    #[arg(long, global = true, value_delimiter = ',', value_name = "NAME")]
+    only: Vec<String>,
+
+    /// Skip these tools / scan kinds (comma-separated or repeated)
+    #[arg(long, global = true, value_delimiter = ',', value_name = "NAME")]
+    skip: Vec<String>,
+
+    /// Rescan every file instead of reusing cached privacy findings (`[scan].cache`)
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Use this config file instead of discovering run_checks.toml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Output format: colored text tables, or one versioned JSON document on stdout
+    #[arg(long, value_enum, default_value_t = report::OutputFormat::Text, global = true)]
+    format: report::OutputFormat,
+
     #[command(subcommand)]
     cmd: CommandKind,
 }
 
+/// Report files a checks run can write in addition to stdout.
+#[derive(Args)]
+struct ReportArgs {
+    /// Also write a JUnit XML report to this path
+    #[arg(long)]
+    junit: Option<PathBuf>,
+
+    /// Also write a SARIF 2.1.0 log of privacy/secret findings to this path
+    #[arg(long)]
+    sarif: Option<PathBuf>,
+}
+
 #[derive(Subcommand)]
 enum CommandKind {
     /// Run rustfmt, clippy, check, test. Prints tables. Extra scans are skipped. Copies to clipboard.
-    Checks,
+    Checks {
+        #[command(flatten)]
+        reports: ReportArgs,
+    },
 
     /// Same as `checks` but runs the Extra scans row. Copies to clipboard.
     #[command(name = "checks-extras", visible_alias = "Checks plus Extras")]
-    ChecksExtras,
+    ChecksExtras {
+        #[command(flatten)]
+        reports: ReportArgs,
+    },
 
     /// Create default project files/folders if absent. Copies to clipboard.
     #[command(
@@ -48,10 +140,10 @@ enum CommandKind {
     )]
     CreateDefaults,
 
-    /// Print a directory tree. Default depth=2. Copies to clipboard.
+    /// Print a directory tree. Default depth=2 (or `[tree].depth`). Copies to clipboard.
     Tree {
-        #[arg(long, default_value_t = 2)]
-        depth: usize,
+        #[arg(long)]
+        depth: Option<usize>,
     },
 
     /// Print all .rs/.md/.sh/.toml files. Copies to clipboard.
@@ -59,89 +151,279 @@ enum CommandKind {
 
     /// Run `checks` (skip extras), then `files`, then `tree`. One clipboard copy with all sections.
     All {
-        #[arg(long, default_value_t = 2)]
-        depth: usize,
+        #[arg(long)]
+        depth: Option<usize>,
+
+        #[command(flatten)]
+        reports: ReportArgs,
+    },
+
+    /// Run `checks`, then re-run on every change: `.rs`/`Cargo.toml` changes re-run
+    /// everything, `run_checks.toml` is reloaded first, other project files only
+    /// re-run the privacy scans. No clipboard copy.
+    Watch {
+        /// Include the Extra scans row, as in `checks-extras`
+        #[arg(long)]
+        extras: bool,
+
+        /// Wait this long after the last change before running
+        #[arg(long, default_value_t = 300, value_name = "MS")]
+        debounce: u64,
     },
+
+    /// Manage the privacy findings baseline (`[scan].baseline`).
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineAction {
+    /// Run every privacy scan and record the current findings as accepted;
+    /// later runs report only new ones as found. With `--only`/`--skip`, only
+    /// the selected scan kinds are re-recorded and the rest are kept.
+    Write,
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let json = cli.format == report::OutputFormat::Json;
+    let (cfg, cfg_path) = match load_config(&cli) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    let selection = run_checks::Selection { only: cli.only.clone(), skip: cli.skip.clone() };
+    if let Err(e) = selection.validate(&cfg) {
+        eprintln!("{e}");
+        std::process::exit(2);
+    }
     let mut exit_code = 0usize;
 
     match cli.cmd {
-        CommandKind::Checks => {
-            util::maybe_clear(cli.clear);
-            let (ok, blob) = run_checks::run_checks(false).await;
-            println!("{blob}");
-            util::copy_report("checks", &blob);
-            if !ok {
+        CommandKind::Checks { reports } => {
+            util::maybe_clear(cli.clear && !json);
+            let checks = run_checks::run_checks(&cfg, false, cli.fmt, &selection).await;
+            if !write_reports(&reports, &checks) {
+                exit_code = 1;
+            }
+            if json {
+                report::JsonReport {
+                    ok: checks.ok,
+                    checks: Some(&checks),
+                    ..report::JsonReport::new("checks")
+                }
+                .print();
+            } else {
+                let blob = checks.render();
+                println!("{blob}");
+                util::copy_report("checks", &blob);
+            }
+            if !checks.ok {
                 eprintln!("Some checks failed.");
                 exit_code = 1;
             }
         }
-        CommandKind::ChecksExtras => {
-            util::maybe_clear(cli.clear);
-            let (ok, blob) = run_checks::run_checks(true).await;
-            println!("{blob}");
-            util::copy_report("checks-extras", &blob);
-            if !ok {
+        CommandKind::ChecksExtras { reports } => {
+            util::maybe_clear(cli.clear && !json);
+            let checks = run_checks::run_checks(&cfg, true, cli.fmt, &selection).await;
+            if !write_reports(&reports, &checks) {
+                exit_code = 1;
+            }
+            if json {
+                report::JsonReport {
+                    ok: checks.ok,
+                    checks: Some(&checks),
+                    ..report::JsonReport::new("checks-extras")
+                }
+                .print();
+            } else {
+                let blob = checks.render();
+                println!("{blob}");
+                util::copy_report("checks-extras", &blob);
+            }
+            if !checks.ok {
                 eprintln!("Some checks failed.");
                 exit_code = 1;
             }
         }
         CommandKind::CreateDefaults => {
-            util::maybe_clear(cli.clear);
-            let out = defaults::ensure_defaults();
-            println!("{out}");
-            util::copy_report("create-defaults", &out);
+            util::maybe_clear(cli.clear && !json);
+            let actions = defaults::ensure_defaults();
+            if json {
+                report::JsonReport {
+                    ok: actions.iter().all(|a| a.action != defaults::Action::Error),
+                    defaults: Some(&actions),
+                    ..report::JsonReport::new("create-defaults")
+                }
+                .print();
+            } else {
+                let out = defaults::render_defaults(&actions);
+                println!("{out}");
+                util::copy_report("create-defaults", &out);
+            }
         }
         CommandKind::Tree { depth } => {
-            util::maybe_clear(cli.clear);
-            let blob = tree::collect_tree(depth);
-            println!("{blob}");
-            util::copy_report("tree", &blob);
+            util::maybe_clear(cli.clear && !json);
+            let tree = tree::collect_tree(&cfg, depth.unwrap_or(cfg.tree.depth));
+            if json {
+                report::JsonReport {
+                    ok: tree.error.is_none(),
+                    tree: Some(&tree),
+                    ..report::JsonReport::new("tree")
+                }
+                .print();
+            } else {
+                let blob = tree::render_tree(&tree);
+                println!("{blob}");
+                util::copy_report("tree", &blob);
+            }
         }
         CommandKind::Files => {
-            util::maybe_clear(cli.clear);
-            let blob = display_all::collect_all_rs();
-            print!("{blob}");
-            util::copy_report("files", &blob);
+            util::maybe_clear(cli.clear && !json);
+            let files = display_all::collect_files(&cfg);
+            if json {
+                report::JsonReport { files: Some(&files), ..report::JsonReport::new("files") }
+                    .print();
+            } else {
+                let blob = display_all::render_files(&cfg, &files);
+                print!("{blob}");
+                util::copy_report("files", &blob);
+            }
         }
-        CommandKind::All { depth } => {
-            util::maybe_clear(cli.clear);
+        CommandKind::All { depth, reports } => {
+            util::maybe_clear(cli.clear && !json);
 
             // 1) checks
-            let (ok, checks_blob) = run_checks::run_checks(false).await;
-            println!("{checks_blob}");
-            if !ok {
+            let checks = run_checks::run_checks(&cfg, false, cli.fmt, &selection).await;
+            if !write_reports(&reports, &checks) {
+                exit_code = 1;
+            }
+            if !checks.ok {
                 eprintln!("[all] Checks failed, continuing with files/tree.");
                 exit_code = 1;
             }
 
             // 2) files
-            let files_blob = display_all::collect_all_rs();
-            print!("{files_blob}");
+            let files = display_all::collect_files(&cfg);
 
             // 3) tree
-            let tree_blob = tree::collect_tree(depth);
-            println!("{tree_blob}");
-
-            // One combined clipboard copy
-            let mut all_blob = String::new();
-            all_blob.push_str(&checks_blob);
-            if !all_blob.ends_with('\n') {
-                all_blob.push('\n');
+            let tree = tree::collect_tree(&cfg, depth.unwrap_or(cfg.tree.depth));
+
+            if json {
+                report::JsonReport {
+                    ok: checks.ok,
+                    checks: Some(&checks),
+                    files: Some(&files),
+                    tree: Some(&tree),
+                    ..report::JsonReport::new("all")
+                }
+                .print();
+            } else {
+                let checks_blob = checks.render();
+                println!("{checks_blob}");
+                let files_blob = display_all::render_files(&cfg, &files);
+                print!("{files_blob}");
+                let tree_blob = tree::render_tree(&tree);
+                println!("{tree_blob}");
+
+                // One combined clipboard copy
+                let mut all_blob = String::new();
+                all_blob.push_str(&checks_blob);
+                if !all_blob.ends_with('\n') {
+                    all_blob.push('\n');
+                }
+                all_blob.push_str(&files_blob);
+                if !all_blob.ends_with('\n') {
+                    all_blob.push('\n');
+                }
+                all_blob.push_str(&tree_blob);
+
+                util::copy_report("all", &all_blob);
             }
-            all_blob.push_str(&files_blob);
-            if !all_blob.ends_with('\n') {
-                all_blob.push('\n');
+        }
+        CommandKind::Watch { extras, debounce } => {
+            let opts = watch::WatchOptions {
+                run_extras: extras,
+                fmt_mode: cli.fmt,
+                debounce: std::time::Duration::from_millis(debounce),
+                clear: cli.clear && !json,
+                format: cli.format,
+            };
+            let reload = || load_config(&cli).map(|(cfg, _)| cfg);
+            if let Err(e) = watch::watch(cfg, cfg_path, &selection, opts, reload).await {
+                eprintln!("{e}");
+                exit_code = 2;
+            }
+        }
+        CommandKind::Baseline { action: BaselineAction::Write } => {
+            match run_checks::write_baseline(&cfg, &selection) {
+                Ok(written) if json => report::JsonReport {
+                    baseline: Some(&written),
+                    ..report::JsonReport::new("baseline write")
+                }
+                .print(),
+                Ok(written) => {
+                    println!("Recorded {} finding(s) in {}", written.findings, written.path)
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    exit_code = 2;
+                }
             }
-            all_blob.push_str(&tree_blob);
-
-            util::copy_report("all", &all_blob);
         }
     }
 
     std::process::exit(exit_code as i32);
 }
+
+/// Load `run_checks.toml` (or `--config`) and apply the command-line overrides.
+fn load_config(cli: &Cli) -> Result<(config::Config, Option<PathBuf>), String> {
+    let (mut cfg, path) = config::Config::load(cli.config.as_deref())?;
+    if cli.timeout.is_some() {
+        cfg.tools.timeout_secs = cli.timeout;
+    }
+    if cli.run_timeout.is_some() {
+        cfg.tools.run_timeout_secs = cli.run_timeout;
+    }
+    if let Some(jobs) = cli.jobs {
+        cfg.tools.jobs = Some(jobs as usize);
+    }
+    if cli.feature_matrix {
+        cfg.matrix.features = true;
+    }
+    if let Some(toolchains) = &cli.toolchains {
+        cfg.matrix.toolchains = toolchains.clone();
+    }
+    if cli.no_cache {
+        cfg.scan.cache = false;
+    }
+    if cli.fail_fast {
+        cfg.tools.on_failure = config::FailurePolicy::FailFast;
+    } else if cli.keep_going {
+        cfg.tools.on_failure = config::FailurePolicy::KeepGoing;
+    }
+    Ok((cfg, path))
+}
+
+/// Write the requested JUnit/SARIF files; returns false if any write failed.
+fn write_reports(reports: &ReportArgs, checks: &run_checks::ChecksReport) -> bool {
+    let mut ok = true;
+    if let Some(path) = &reports.junit {
+        if let Err(e) = junit::write_junit(path, checks) {
+            eprintln!("Failed to write JUnit report to {}: {e}", path.display());
+            ok = false;
+        }
+    }
+    if let Some(path) = &reports.sarif {
+        if let Err(e) = sarif::write_sarif(path, checks) {
+            eprintln!("Failed to write SARIF log to {}: {e}", path.display());
+            ok = false;
+        }
+    }
+    ok
+}
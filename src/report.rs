@@ -0,0 +1,63 @@
+// Package run_checks
+// File: src/report.rs
+
+use crate::defaults::DefaultAction;
+use crate::display_all::FileEntry;
+use crate::run_checks::{BaselineWritten, ChecksReport};
+use crate::tree::Tree;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Bump when a field is removed/renamed or its meaning changes; adding fields is compatible.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How results are written to stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored tables and sections; also copied to the clipboard.
+    #[default]
+    Text,
+    /// One JSON document on stdout; no clipboard copy.
+    Json,
+}
+
+/// Top-level JSON document, one per invocation. Sections a subcommand does not
+/// produce are omitted.
+#[derive(Serialize)]
+pub struct JsonReport<'a> {
+    pub schema_version: u32,
+    pub command: &'a str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<&'a ChecksReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<&'a [DefaultAction]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<&'a [FileEntry]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree: Option<&'a Tree>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<&'a BaselineWritten>,
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn new(command: &'a str) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            command,
+            ok: true,
+            checks: None,
+            defaults: None,
+            files: None,
+            tree: None,
+            baseline: None,
+        }
+    }
+
+    pub fn print(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(s) => println!("{s}"),
+            Err(e) => eprintln!("failed to serialize JSON report: {e}"),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Snippet
+// File: src/run_checks/allow.rs
+
+use crate::config::ScanConfig;
+use globset::GlobSet;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// `[scan.allow]` compiled: per rule id (or `"*"`) the values, regexes and
+/// path globs whose matches are never reported.
+pub(super) struct Allowlist {
+    lists: Vec<(String, Compiled)>,
+}
+
+struct Compiled {
+    values: BTreeSet<String>,
+    patterns: Vec<Regex>,
+    paths: GlobSet,
+}
+
+impl Allowlist {
+    /// Entries were validated when the config was loaded; any that still
+    /// fail to compile are dropped.
+    pub(super) fn new(scan: &ScanConfig) -> Allowlist {
+        let lists = scan
+            .allow
+            .iter()
+            .map(|(rule, a)| {
+                let compiled = Compiled {
+                    values: a.values.iter().cloned().collect(),
+                    patterns: a.patterns.iter().filter_map(|p| Regex::new(p).ok()).collect(),
+                    paths: a.path_globs().unwrap_or_else(|_| GlobSet::empty()),
+                };
+                (rule.clone(), compiled)
+            })
+            .collect();
+        Allowlist { lists }
+    }
+
+    fn for_rule<'a>(&'a self, rule: &'a str) -> impl Iterator<Item = &'a Compiled> + 'a {
+        self.lists.iter().filter(move |(r, _)| r == "*" || r == rule).map(|(_, c)| c)
+    }
+
+    /// Whether `text`, matched by `rule`, is a known-safe value.
+    pub(super) fn allows_value(&self, rule: &str, text: &str) -> bool {
+        self.for_rule(rule)
+            .any(|c| c.values.contains(text) || c.patterns.iter().any(|re| re.is_match(text)))
+    }
+
+    /// Whether findings of `rule` in `path` (as scanned, e.g. `./src/x.rs`) are ignored.
+    pub(super) fn allows_path(&self, rule: &str, path: &str) -> bool {
+        let rel = Path::new(path);
+        let rel = rel.strip_prefix(".").unwrap_or(rel);
+        self.for_rule(rule).any(|c| c.paths.is_match(rel))
+    }
+}
@@ -0,0 +1,148 @@
+// Snippet
+// File: src/run_checks/baseline.rs
+
+use super::privacy::{collect_rows, rule_scanned, Finding, SCAN_KINDS};
+use super::scan_cache::ruleset_hash;
+use super::Selection;
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Bump when fingerprints are computed differently.
+const BASELINE_VERSION: u32 = 1;
+
+/// Accepted findings recorded by `run_checks baseline write`. Later runs
+/// report matching findings as baselined instead of found.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<BaselineEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub rule: String,
+    pub path: String,
+    pub fingerprint: String,
+}
+
+/// What `baseline write` recorded, for the JSON report.
+#[derive(Debug, Serialize)]
+pub struct BaselineWritten {
+    pub path: String,
+    pub findings: usize,
+}
+
+impl Baseline {
+    /// `Ok(None)` when there is no baseline file.
+    pub(super) fn load(path: &Path) -> Result<Option<Baseline>, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+        };
+        let b: Baseline =
+            serde_json::from_str(&text).map_err(|e| format!("invalid {}: {e}", path.display()))?;
+        if b.version != BASELINE_VERSION {
+            return Err(format!(
+                "{} has version {}, expected {BASELINE_VERSION}; run `run_checks baseline write`",
+                path.display(),
+                b.version
+            ));
+        }
+        Ok(Some(b))
+    }
+}
+
+/// Baseline entries not yet matched in this run. Identical lines in one file
+/// share a fingerprint, so each entry absorbs one finding.
+pub(super) struct Remaining {
+    left: BTreeMap<(String, String, String), usize>,
+}
+
+impl Remaining {
+    pub(super) fn new(b: &Baseline) -> Remaining {
+        let mut left = BTreeMap::new();
+        for e in &b.findings {
+            *left.entry((e.rule.clone(), e.path.clone(), e.fingerprint.clone())).or_default() += 1;
+        }
+        Remaining { left }
+    }
+
+    /// Split findings into (new, baselined).
+    pub(super) fn split(&mut self, findings: Vec<Finding>) -> (Vec<Finding>, Vec<Finding>) {
+        findings.into_iter().partition(|f| {
+            let key = (f.rule.clone(), f.path.clone(), f.fingerprint.clone());
+            match self.left.get_mut(&key) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    false
+                }
+                _ => true,
+            }
+        })
+    }
+
+    /// Entries never matched, for rules `scanned` says ran this time.
+    pub(super) fn resolved(&self, scanned: impl Fn(&str) -> bool) -> Vec<BaselineEntry> {
+        self.left
+            .iter()
+            .filter(|((rule, _, _), _)| scanned(rule))
+            .flat_map(|((rule, path, fingerprint), &n)| {
+                let e = BaselineEntry {
+                    rule: rule.clone(),
+                    path: path.clone(),
+                    fingerprint: fingerprint.clone(),
+                };
+                std::iter::repeat_n(e, n)
+            })
+            .collect()
+    }
+}
+
+/// Fingerprint of a finding that survives edits elsewhere in the file: the
+/// rule, the path and the line's text with whitespace collapsed. The line
+/// number is left out, and the hash keeps secrets out of the baseline file.
+pub(super) fn fingerprint(rule: &str, path: &str, line: Option<&str>) -> String {
+    let normalized = line.map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "));
+    let mut parts = vec![rule, path];
+    parts.extend(normalized.as_deref());
+    format!("{:016x}", ruleset_hash(&parts))
+}
+
+/// Scan with the Extra scans on and no baseline applied, and record every
+/// reported (non-suppressed) finding to `[scan].baseline`. Entries for scan
+/// kinds that `selection` filtered out are kept from the existing file.
+pub fn write_baseline(cfg: &Config, selection: &Selection) -> Result<BaselineWritten, String> {
+    let path = PathBuf::from(&cfg.scan.baseline);
+    let partial = !SCAN_KINDS.iter().all(|k| selection.allows(k));
+    let kept = match Baseline::load(&path) {
+        Ok(existing) => existing.map(|b| b.findings).unwrap_or_default(),
+        // A full rewrite replaces an unreadable or outdated file; a partial
+        // one would lose the entries it cannot read.
+        Err(e) if partial => return Err(e),
+        Err(_) => Vec::new(),
+    };
+    let rows = collect_rows(cfg, true, selection, None);
+    let mut findings: Vec<BaselineEntry> = kept
+        .into_iter()
+        .filter(|e| !rule_scanned(&e.rule, true, selection))
+        .chain(rows.iter().flat_map(|r| &r.findings).map(|f| BaselineEntry {
+            rule: f.rule.clone(),
+            path: f.path.clone(),
+            fingerprint: f.fingerprint.clone(),
+        }))
+        .collect();
+    findings.sort();
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+    }
+    let n = findings.len();
+    let text = serde_json::to_string_pretty(&Baseline { version: BASELINE_VERSION, findings })
+        .map_err(|e| format!("failed to serialize baseline: {e}"))?;
+    std::fs::write(&path, text + "\n")
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(BaselineWritten { path: path.display().to_string(), findings: n })
+}
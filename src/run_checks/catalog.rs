@@ -0,0 +1,294 @@
+// Snippet
+// File: src/run_checks/catalog.rs
+
+use serde::{Deserialize, Serialize};
+
+/// How bad a finding is if it is real. Maps onto SARIF levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// One named detector. `version` is bumped whenever what it matches changes,
+/// so baselines and reports can tell old results from new ones.
+#[derive(Clone, Copy, Debug)]
+pub struct Rule {
+    pub id: &'static str,
+    pub version: u32,
+    pub severity: Severity,
+    pub description: &'static str,
+    /// Line regex for the Extra scans; `None` for rules with their own logic
+    /// (candidates, leak files, PII, entropy). When the regex has a capture
+    /// group, group 1 is the reported span.
+    pub pattern: Option<&'static str>,
+    /// Only report a match when this regex matches within `NEAR_LINES` lines.
+    pub near: Option<&'static str>,
+}
+
+/// How far `Rule::near` looks, in lines either side of a match.
+pub const NEAR_LINES: usize = 5;
+
+const fn rule(
+    id: &'static str,
+    version: u32,
+    severity: Severity,
+    description: &'static str,
+) -> Rule {
+    Rule { id, version, severity, description, pattern: None, near: None }
+}
+
+const fn line_rule(
+    id: &'static str,
+    version: u32,
+    severity: Severity,
+    description: &'static str,
+    pattern: &'static str,
+) -> Rule {
+    Rule { id, version, severity, description, pattern: Some(pattern), near: None }
+}
+
+/// Every rule id the scanner can emit. `[scan].patterns` may override the
+/// pattern of a line rule or add new ids; `[scan].disabled_rules` drops any.
+pub const RULES: &[Rule] = &[
+    rule("username", 1, Severity::Low, "Local username found in project files"),
+    rule("hostname", 1, Severity::Low, "Local hostname found in project files"),
+    rule("ip-address", 1, Severity::Low, "Local non-loopback IP address found in project files"),
+    line_rule(
+        "secret-keyword",
+        1,
+        Severity::Low,
+        "Credential keyword (api, secret, token, key, password, ...)",
+        r"(?i)\b(api|secret|token|key|password|passwd|bearer|authorization)\b",
+    ),
+    line_rule(
+        "aws-access-key",
+        2,
+        Severity::High,
+        "AWS access key id (AKIA... long-term, ASIA... temporary)",
+        r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    ),
+    Rule {
+        near: Some(r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b|(?i)aws_?secret_?access_?key"),
+        ..line_rule(
+            "aws-secret-key",
+            1,
+            Severity::Critical,
+            "AWS secret access key (40 characters next to an access key id)",
+            r"(?:^|[^A-Za-z0-9/+])([A-Za-z0-9/+]{40})(?:[^A-Za-z0-9/+=]|$)",
+        )
+    },
+    line_rule(
+        "github-token",
+        1,
+        Severity::High,
+        "GitHub classic personal access token (ghp_...)",
+        r"ghp_[A-Za-z0-9]{36,}",
+    ),
+    line_rule(
+        "github-fine-grained-pat",
+        1,
+        Severity::High,
+        "GitHub fine-grained personal access token (github_pat_...)",
+        r"github_pat_[A-Za-z0-9_]{60,}",
+    ),
+    line_rule(
+        "github-oauth-token",
+        1,
+        Severity::High,
+        "GitHub OAuth access token (gho_...)",
+        r"gho_[A-Za-z0-9]{36,}",
+    ),
+    line_rule(
+        "github-app-token",
+        1,
+        Severity::High,
+        "GitHub App installation or user-to-server token (ghs_..., ghu_...)",
+        r"gh[su]_[A-Za-z0-9]{36,}",
+    ),
+    line_rule(
+        "gitlab-pat",
+        1,
+        Severity::High,
+        "GitLab personal access token (glpat-...)",
+        r"glpat-[A-Za-z0-9_-]{20,}",
+    ),
+    line_rule(
+        "stripe-secret-key",
+        1,
+        Severity::Critical,
+        "Stripe live secret key (sk_live_...)",
+        r"sk_live_[A-Za-z0-9]{24,}",
+    ),
+    line_rule(
+        "google-api-key",
+        1,
+        Severity::High,
+        "Google API key (AIza...)",
+        r"AIza[0-9A-Za-z_-]{35}",
+    ),
+    line_rule(
+        "slack-token",
+        1,
+        Severity::High,
+        "Slack token (xox?-...)",
+        r"xox[baprs]-[A-Za-z0-9-]{10,}",
+    ),
+    line_rule(
+        "slack-webhook",
+        1,
+        Severity::Medium,
+        "Slack incoming webhook URL",
+        r"https://hooks\.slack\.com/services/T[A-Z0-9]+/B[A-Z0-9]+/[A-Za-z0-9]+",
+    ),
+    line_rule(
+        "npm-token",
+        1,
+        Severity::High,
+        "npm access token (npm_...)",
+        r"\bnpm_[A-Za-z0-9]{36}\b",
+    ),
+    line_rule(
+        "pem-block",
+        2,
+        Severity::Critical,
+        "PEM private key block (PKCS#8, encrypted, RSA, DSA, EC, OpenSSH, PGP)",
+        r"BEGIN (?:(?:RSA|DSA|EC|OPENSSH|ENCRYPTED|PGP) )?PRIVATE KEY",
+    ),
+    line_rule(
+        "jwt",
+        1,
+        Severity::Medium,
+        "JSON Web Token",
+        r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+    ),
+    line_rule(
+        "database-url-password",
+        1,
+        Severity::High,
+        "Database connection URL with an embedded password",
+        r#"\b(?:postgres(?:ql)?|mysql|mariadb|mongodb(?:\+srv)?|rediss?|amqps?|mssql|sqlserver)://[^\s:/@"']+:[^\s:/@"']+@"#,
+    ),
+    rule(
+        "high-entropy-string",
+        1,
+        Severity::Medium,
+        "Random-looking quoted or assigned token (likely a secret)",
+    ),
+    rule(
+        "leak-file",
+        1,
+        Severity::Medium,
+        "File that commonly holds credentials (.env, *.pem, kubeconfig, ...)",
+    ),
+    rule("pii", 1, Severity::Low, "Personal data keyword in docs/examples/tests"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanConfig;
+    use crate::run_checks::scanner::LineScanner;
+
+    // Secret-shaped samples are assembled at run time so this file does not
+    // trip the scanner itself.
+    fn aws_secret() -> String {
+        ["wJalrXUtnFEMI/", "K7MDENG/bPxRf", "iCYEXAMPLEKEY"].concat()
+    }
+
+    fn samples() -> Vec<(&'static str, String, String)> {
+        let alnum = |n: usize| "A1b2C3d4E5".chars().cycle().take(n).collect::<String>();
+        vec![
+            ("secret-keyword", "password = x".into(), "passwords are long".into()),
+            (
+                "aws-access-key",
+                format!("id = {}{}", "AKIA", "IOSFODNN7EXAMPLE"),
+                format!("id = {}{}", "AKIA", "1234"),
+            ),
+            (
+                "aws-secret-key",
+                format!("aws_secret_access_key = {}", aws_secret()),
+                format!("checksum = {}", aws_secret()),
+            ),
+            ("github-token", format!("ghp_{}", alnum(36)), format!("ghp_{}", alnum(10))),
+            (
+                "github-fine-grained-pat",
+                format!("github_pat_{}", alnum(60)),
+                format!("github_pat_{}", alnum(20)),
+            ),
+            ("github-oauth-token", format!("gho_{}", alnum(36)), format!("gho_{}", alnum(10))),
+            ("github-app-token", format!("ghs_{}", alnum(36)), format!("ghx_{}", alnum(36))),
+            ("gitlab-pat", format!("glpat-{}", alnum(20)), format!("glpat-{}", alnum(8))),
+            (
+                "stripe-secret-key",
+                format!("sk_live_{}", alnum(24)),
+                format!("sk_test_{}", alnum(24)),
+            ),
+            ("google-api-key", format!("AIza{}", alnum(35)), format!("AIza{}", alnum(10))),
+            ("slack-token", format!("xoxb-{}", alnum(12)), format!("xoxz-{}", alnum(12))),
+            (
+                "slack-webhook",
+                format!("https://hooks.slack.com/services/{}/B0001/{}", "T0001", alnum(8)),
+                "https://hooks.slack.com/services/".into(),
+            ),
+            ("npm-token", format!("npm_{}", alnum(36)), "npm_install".into()),
+            (
+                "pem-block",
+                format!("-----BEGIN RSA {} KEY-----", "PRIVATE"),
+                "-----BEGIN PUBLIC KEY-----".into(),
+            ),
+            (
+                "jwt",
+                format!("eyJ{}.eyJ{}.{}", alnum(12), alnum(12), alnum(12)),
+                format!("eyJ{}", alnum(12)),
+            ),
+            (
+                "database-url-password",
+                format!("postgres://app:{}{}/db", "pw", "@db.example"),
+                "postgres://db.example/app".into(),
+            ),
+        ]
+    }
+
+    fn hits(rule: &str, content: &str) -> bool {
+        let scanner = LineScanner::new(&ScanConfig::default());
+        scanner.scan_file("./src/lib.rs", content).iter().any(|f| f.rule == rule)
+    }
+
+    #[test]
+    fn every_line_rule_has_samples() {
+        let samples = samples();
+        for r in RULES.iter().filter(|r| r.pattern.is_some()) {
+            assert!(samples.iter().any(|(id, _, _)| *id == r.id), "no samples for {}", r.id);
+        }
+    }
+
+    #[test]
+    fn line_rules_match_positive_samples_only() {
+        for (id, positive, negative) in samples() {
+            assert!(hits(id, &positive), "{id} missed {positive:?}");
+            assert!(!hits(id, &negative), "{id} matched {negative:?}");
+        }
+    }
+
+    #[test]
+    fn rule_ids_are_unique() {
+        for (i, r) in RULES.iter().enumerate() {
+            assert!(!RULES[..i].iter().any(|o| o.id == r.id), "duplicate rule {}", r.id);
+        }
+    }
+}
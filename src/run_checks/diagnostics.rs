@@ -0,0 +1,142 @@
+// Snippet
+// File: src/run_checks/diagnostics.rs
+
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How many locations the Details column lists before collapsing to `+N more`.
+const TOP_LOCATIONS: usize = 5;
+
+/// One compiler/clippy diagnostic, reduced to its primary span.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub level: String,
+    pub code: Option<String>,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+// Subset of cargo's `--message-format=json` schema that we read.
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    level: String,
+    message: String,
+    code: Option<CompilerCode>,
+    #[serde(default)]
+    spans: Vec<CompilerSpan>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompilerCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct CompilerSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+/// Split cargo JSON output into parsed diagnostics and human-readable text.
+///
+/// The text keeps every non-JSON line plus each message's `rendered` form, so
+/// the per-tool output section still reads like plain cargo output.
+/// Diagnostics without a primary span (e.g. "aborting due to ...") are not
+/// counted but still appear in the text. Duplicates reported for several
+/// targets (lib + test) are collapsed.
+pub fn parse_cargo_messages(stdout: &str) -> (Vec<Diagnostic>, String) {
+    let mut diags: Vec<Diagnostic> = Vec::new();
+    let mut text = String::new();
+
+    for line in stdout.lines() {
+        let Ok(msg) = serde_json::from_str::<CargoMessage>(line) else {
+            text.push_str(line);
+            text.push('\n');
+            continue;
+        };
+        if msg.reason != "compiler-message" {
+            continue;
+        }
+        let Some(m) = msg.message else {
+            continue;
+        };
+        if let Some(r) = &m.rendered {
+            text.push_str(r);
+        }
+        let Some(span) = m.spans.iter().find(|s| s.is_primary) else {
+            continue;
+        };
+        let d = Diagnostic {
+            level: m.level,
+            code: m.code.map(|c| c.code),
+            file: span.file_name.clone(),
+            line: span.line_start,
+            column: span.column_start,
+            message: m.message,
+        };
+        if !diags.contains(&d) {
+            diags.push(d);
+        }
+    }
+
+    (diags, text)
+}
+
+/// Details cell: counts by level, then the first few `file:line:col` locations.
+pub fn summarize(diags: &[Diagnostic]) -> String {
+    if diags.is_empty() {
+        return "no diagnostics".to_string();
+    }
+    let mut by_level: BTreeMap<&str, usize> = BTreeMap::new();
+    for d in diags {
+        *by_level.entry(d.level.as_str()).or_default() += 1;
+    }
+    let counts =
+        by_level.iter().map(|(lvl, n)| format!("{lvl}: {n}")).collect::<Vec<_>>().join(", ");
+
+    let locs: Vec<String> = diags
+        .iter()
+        .take(TOP_LOCATIONS)
+        .map(|d| format!("{}:{}:{}", d.file, d.line, d.column))
+        .collect();
+    let mut out = format!("{counts}\n{}", locs.join("\n"));
+    if diags.len() > TOP_LOCATIONS {
+        out.push_str(&format!("\n+{} more", diags.len() - TOP_LOCATIONS));
+    }
+    out
+}
+
+/// Full listing, one diagnostic per line, for the report body.
+pub fn render_section(tool: &str, diags: &[Diagnostic]) -> String {
+    let mut out = format!(
+        "{}\n{tool} diagnostics\n{}\n",
+        "========================================".green(),
+        "========================================".green()
+    );
+    if diags.is_empty() {
+        out.push_str("(none)");
+        return out;
+    }
+    let lines: Vec<String> = diags
+        .iter()
+        .map(|d| {
+            let code = d.code.as_deref().map(|c| format!("[{c}]")).unwrap_or_default();
+            format!("{}{code} {}:{}:{}: {}", d.level, d.file, d.line, d.column, d.message)
+        })
+        .collect();
+    out.push_str(&lines.join("\n"));
+    out
+}
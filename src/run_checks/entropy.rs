@@ -0,0 +1,119 @@
+// Snippet
+// File: src/run_checks/entropy.rs
+
+use crate::config::EntropyConfig;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+pub(super) const RULE: &str = "high-entropy-string";
+
+/// A token of `n` characters has at most `log2(n)` bits of entropy, so a
+/// fixed threshold would never fire on short tokens. The threshold is capped
+/// at this share of the most a token of its length and alphabet can reach.
+const LENGTH_SCALE: f64 = 0.85;
+
+/// A quoted or assigned token that looks random, with its byte range in the line.
+pub(super) struct EntropyHit {
+    pub range: std::ops::Range<usize>,
+    /// The token redacted to its first four characters, plus its length and entropy.
+    pub redacted: String,
+}
+
+/// Tokens in `line` that start after a quote or an `=`/`:` and whose entropy
+/// reaches the threshold for their alphabet and length (see `threshold`).
+/// Hex tokens must mix digits and letters; other tokens must contain a
+/// digit, which keeps long words out.
+pub(super) fn scan_line(cfg: &EntropyConfig, line: &str) -> Vec<EntropyHit> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r#"(?:["'`]|[=:]\s*)([A-Za-z0-9+/_-]+={0,2})"#).expect("valid regex")
+    });
+    let mut hits = Vec::new();
+    for caps in re.captures_iter(line) {
+        let m = caps.get(1).expect("group 1 always participates");
+        let token = m.as_str();
+        if token.len() < cfg.min_length || !token.bytes().any(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        let hex = token.bytes().all(|b| b.is_ascii_hexdigit());
+        let threshold = match hex {
+            true if !token.bytes().any(|b| b.is_ascii_alphabetic()) => continue,
+            true => threshold(cfg.hex_threshold, 16, token.len()),
+            false => threshold(cfg.base64_threshold, 64, token.len()),
+        };
+        let bits = shannon(token);
+        if bits >= threshold {
+            let kind = if hex { "hex" } else { "base64" };
+            let redacted = format!(
+                "{}… ({} chars, {kind} entropy {bits:.2})",
+                &token[..4.min(token.len())],
+                token.len()
+            );
+            hits.push(EntropyHit { range: m.range(), redacted });
+        }
+    }
+    hits
+}
+
+/// `configured`, lowered for tokens too short to reach it: at most
+/// `LENGTH_SCALE * log2(min(len, alphabet))`.
+fn threshold(configured: f64, alphabet: usize, len: usize) -> f64 {
+    configured.min(LENGTH_SCALE * (len.min(alphabet) as f64).log2())
+}
+
+/// Shannon entropy in bits per character.
+fn shannon(s: &str) -> f64 {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let n = s.chars().count() as f64;
+    counts
+        .values()
+        .map(|&c| {
+            let p = c as f64 / n;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Random-looking samples are assembled at run time so this file does not
+    // trip the scanner itself.
+    fn flagged(token: &str) -> bool {
+        !scan_line(&EntropyConfig::default(), &format!("key = \"{token}\"")).is_empty()
+    }
+
+    #[test]
+    fn short_random_tokens_are_flagged_from_min_length() {
+        let distinct = format!("{}{}", "aB3dE5gH7jK9m", "N1pQ2rStUvWxYz");
+        assert!(!flagged(&distinct[..19]));
+        assert!(flagged(&distinct[..20]));
+        assert!(flagged(&distinct[..22]));
+        assert!(flagged(&distinct[..23]));
+    }
+
+    #[test]
+    fn repetitive_tokens_are_not_flagged() {
+        assert!(!flagged("aaaa1111bbbb2222cccc"));
+        assert!(!flagged("version_1_of_the_name"));
+        assert!(!flagged("deadbeef12deadbeef12"));
+    }
+
+    #[test]
+    fn hex_tokens_use_the_hex_threshold() {
+        assert!(flagged(&format!("{}{}", "3f9a0c71e4", "b25d86af13")));
+        assert!(!flagged("12345678901234567890"));
+    }
+
+    #[test]
+    fn threshold_is_capped_by_length() {
+        assert_eq!(threshold(4.5, 64, 64), 4.5);
+        assert!(threshold(4.5, 64, 20) < 20f64.log2());
+        assert_eq!(threshold(3.0, 16, 40), 3.0);
+    }
+}
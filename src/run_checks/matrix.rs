@@ -0,0 +1,248 @@
+// Snippet
+// File: src/run_checks/matrix.rs
+
+use super::run_tools::{render_result_sections, ToolResult, ToolSpec, ToolStatus};
+use super::{scheduler, Selection};
+use crate::config::Config;
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, Table};
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// One grid of extra runs: a row per variant (feature set or toolchain), a column per tool.
+#[derive(Debug, Serialize)]
+pub struct Matrix {
+    /// What the rows vary: "Features" or "Toolchain".
+    pub axis: String,
+    pub tools: Vec<String>,
+    pub rows: Vec<MatrixRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatrixRow {
+    pub label: String,
+    /// Cargo arguments this row adds, e.g. `--no-default-features --features a` or `+nightly`.
+    pub args: Vec<String>,
+    /// One result per entry of `Matrix::tools`.
+    pub cells: Vec<ToolResult>,
+}
+
+impl Matrix {
+    pub fn all_ok(&self) -> bool {
+        self.rows.iter().flat_map(|r| &r.cells).all(ToolResult::ok)
+    }
+}
+
+/// Feature sets to try: no-default, default, all, then every combination of
+/// `[features]` entries up to `[matrix].powerset_depth`, each without defaults.
+fn feature_variants(cfg: &Config) -> Vec<(String, Vec<String>)> {
+    let none: Vec<String> = Vec::new();
+    let mut variants = vec![
+        ("no-default".to_string(), vec!["--no-default-features".to_string()]),
+        ("default".to_string(), none),
+        ("all".to_string(), vec!["--all-features".to_string()]),
+    ];
+    let features: Vec<String> = read_features()
+        .into_iter()
+        .filter(|f| f != "default" && !cfg.matrix.skip_features.contains(f))
+        .collect();
+    let mut combos: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in 0..cfg.matrix.powerset_depth.min(features.len()) {
+        // Extend each combination with every later feature, so each set appears once.
+        combos = combos
+            .iter()
+            .flat_map(|c| {
+                let start = c.last().map_or(0, |l| l + 1);
+                (start..features.len()).map(move |i| {
+                    let mut c = c.clone();
+                    c.push(i);
+                    c
+                })
+            })
+            .collect();
+        for c in &combos {
+            let list = c.iter().map(|&i| features[i].as_str()).collect::<Vec<_>>().join(",");
+            variants.push((
+                list.clone(),
+                vec!["--no-default-features".to_string(), "--features".to_string(), list],
+            ));
+        }
+    }
+    variants
+}
+
+/// Feature names from `./Cargo.toml`'s `[features]` table, sorted.
+fn read_features() -> Vec<String> {
+    let Ok(text) = std::fs::read_to_string("Cargo.toml") else {
+        return Vec::new();
+    };
+    let Ok(manifest) = text.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    match manifest.get("features") {
+        Some(toml::Value::Table(t)) => t.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Run `[matrix].feature_tools` once per feature set. All cells share the
+/// build lock, so they run one at a time regardless of `[tools].jobs`.
+pub async fn run_feature_matrix(
+    cfg: &Config,
+    selection: &Selection,
+    workspace: bool,
+    cancel: &watch::Receiver<bool>,
+) -> Matrix {
+    let tools: Vec<String> =
+        cfg.matrix.feature_tools.iter().filter(|t| selection.allows(t)).cloned().collect();
+    let variants = feature_variants(cfg);
+    let specs: Vec<ToolSpec> = variants
+        .iter()
+        .flat_map(|(label, args)| {
+            tools
+                .iter()
+                .map(move |t| ToolSpec::variant(&cfg.tools, t, workspace, label, None, Some(args)))
+        })
+        .collect();
+    let refs: Vec<&ToolSpec> = specs.iter().collect();
+    let mut results =
+        scheduler::run_scheduled(&refs, refs.len().max(1), cfg.tools.on_failure, cancel.clone())
+            .await
+            .into_iter();
+
+    let rows = variants
+        .into_iter()
+        .map(|(label, args)| MatrixRow {
+            label,
+            args,
+            cells: tools.iter().filter_map(|_| results.next()).collect(),
+        })
+        .collect();
+    Matrix { axis: "Features".to_string(), tools, rows }
+}
+
+/// Run `[matrix].toolchain_tools` under each of `[matrix].toolchains`. A
+/// toolchain that is not installed (or `msrv` without a `rust-version`)
+/// becomes a row of skipped cells rather than failures.
+pub async fn run_toolchain_matrix(
+    cfg: &Config,
+    selection: &Selection,
+    workspace: bool,
+    cancel: &watch::Receiver<bool>,
+) -> Matrix {
+    let tools: Vec<String> =
+        cfg.matrix.toolchain_tools.iter().filter(|t| selection.allows(t)).cloned().collect();
+    // (label, toolchain, reason the row is skipped)
+    let plan: Vec<(String, String, Option<String>)> = cfg
+        .matrix
+        .toolchains
+        .iter()
+        .map(|tc| {
+            let (label, toolchain) = match tc.as_str() {
+                "msrv" => match read_msrv() {
+                    Some(v) => (format!("msrv ({v})"), v),
+                    None => {
+                        let why = "no `rust-version` in Cargo.toml".to_string();
+                        return (tc.clone(), tc.clone(), Some(why));
+                    }
+                },
+                _ => (tc.clone(), tc.clone()),
+            };
+            let missing = (!toolchain_installed(&toolchain))
+                .then(|| format!("toolchain `{toolchain}` not installed"));
+            (label, toolchain, missing)
+        })
+        .collect();
+
+    let specs: Vec<Vec<ToolSpec>> = plan
+        .iter()
+        .map(|(label, tc, _)| {
+            tools
+                .iter()
+                .map(|t| ToolSpec::variant(&cfg.tools, t, workspace, label, Some(tc), None))
+                .collect()
+        })
+        .collect();
+    let refs: Vec<&ToolSpec> = plan
+        .iter()
+        .zip(&specs)
+        .filter(|((_, _, skip), _)| skip.is_none())
+        .flat_map(|(_, row)| row)
+        .collect();
+    let mut results =
+        scheduler::run_scheduled(&refs, refs.len().max(1), cfg.tools.on_failure, cancel.clone())
+            .await
+            .into_iter();
+
+    let rows = plan
+        .into_iter()
+        .zip(&specs)
+        .map(|((label, tc, skip), row)| MatrixRow {
+            label,
+            args: vec![format!("+{tc}")],
+            cells: row
+                .iter()
+                .filter_map(|spec| match &skip {
+                    Some(why) => Some(ToolResult::skipped(spec, why.clone(), 0.0)),
+                    None => results.next(),
+                })
+                .collect(),
+        })
+        .collect();
+    Matrix { axis: "Toolchain".to_string(), tools, rows }
+}
+
+/// `rust-version` from `./Cargo.toml` (`[package]` or `[workspace.package]`).
+fn read_msrv() -> Option<String> {
+    let text = std::fs::read_to_string("Cargo.toml").ok()?;
+    let manifest = text.parse::<toml::Table>().ok()?;
+    let from = |t: Option<&toml::Value>| {
+        t?.get("rust-version").and_then(|v| v.as_str()).map(str::to_string)
+    };
+    from(manifest.get("package"))
+        .or_else(|| from(manifest.get("workspace").and_then(|w| w.get("package"))))
+}
+
+/// Whether `cargo +<toolchain>` works without rustup installing anything.
+fn toolchain_installed(toolchain: &str) -> bool {
+    std::process::Command::new("cargo")
+        .arg(format!("+{toolchain}"))
+        .arg("--version")
+        .env("RUSTUP_AUTO_INSTALL", "0")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Render the grid, then full sections for every cell that did not pass.
+pub fn render_matrix(m: &Matrix) -> String {
+    let mut t = Table::new();
+    let mut header = vec![m.axis.clone()];
+    header.extend(m.tools.iter().cloned());
+    t.load_preset(UTF8_FULL).set_header(header);
+    for row in &m.rows {
+        let mut cells = vec![Cell::new(&row.label).add_attribute(Attribute::Bold)];
+        for r in &row.cells {
+            let color = match r.status {
+                _ if r.warning() => Color::Yellow,
+                ToolStatus::Success => Color::Green,
+                ToolStatus::Failed => Color::Red,
+                ToolStatus::TimedOut => Color::Magenta,
+                ToolStatus::Skipped | ToolStatus::Cancelled => Color::Yellow,
+            };
+            let first = r.details.lines().next().unwrap_or("");
+            let text = match r.status {
+                ToolStatus::Success if first == "no diagnostics" => "ok".to_string(),
+                _ => first.to_string(),
+            };
+            cells.push(Cell::new(text).fg(color));
+        }
+        t.add_row(cells);
+    }
+
+    let mut out = t.to_string();
+    for r in m.rows.iter().flat_map(|r| &r.cells).filter(|r| !r.ok() || r.warning()) {
+        out.push_str(&render_result_sections(r));
+    }
+    out
+}
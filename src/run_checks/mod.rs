@@ -1,22 +1,197 @@
+mod allow;
+mod baseline;
+mod catalog;
+mod diagnostics;
+mod entropy;
+mod matrix;
+mod parallel;
 mod privacy;
+mod process;
 mod run_tools;
+mod scan_cache;
+mod scanner;
+mod scheduler;
+mod suppress;
+mod test_results;
+mod workspace;
 
-use privacy::build_privacy_security_table;
-use run_tools::run_core_tools_table;
+use crate::config::{Config, FailurePolicy};
+use matrix::{render_matrix, run_feature_matrix, run_toolchain_matrix, Matrix};
+use privacy::{build_privacy_security_table, PrivacyRow};
+use run_tools::{render_core_tools_table, run_core_tools, ToolsRun};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+use workspace::{member_results, render_member_matrix, MemberResult, Workspace};
+
+pub use baseline::{write_baseline, BaselineWritten};
+pub use catalog::{Severity, RULES};
+pub use privacy::{collect_privacy_rows, PrivacyScan, ScanStatus, SCAN_KINDS};
+pub use run_tools::{FmtMode, ToolResult, ToolStatus};
+
+/// Everything one `checks` run produced: core tool results and privacy rows.
+#[derive(Debug, Serialize)]
+pub struct ChecksReport {
+    pub ok: bool,
+    pub tools: ToolsRun,
+    /// Per-member results; empty unless this is a multi-member workspace.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<MemberResult>,
+    /// Feature-set runs, when the feature matrix is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_matrix: Option<Matrix>,
+    /// Toolchain runs, when `[matrix].toolchains` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolchain_matrix: Option<Matrix>,
+    pub privacy: Vec<PrivacyRow>,
+    /// Whether privacy findings were compared against a loaded baseline file.
+    pub baseline_loaded: bool,
+    /// `[tools].run_timeout_secs` passed; the tool rows were cut short.
+    pub run_timed_out: bool,
+}
+
+impl ChecksReport {
+    /// Whether the tool rows and matrices passed within the run timeout.
+    /// `ok` also needs the privacy scan to pass; see `PrivacyScan::ok`.
+    pub fn tools_ok(&self) -> bool {
+        self.tools.all_ok()
+            && self.feature_matrix.iter().chain(&self.toolchain_matrix).all(Matrix::all_ok)
+            && !self.run_timed_out
+    }
+
+    /// Human-readable report: tools table + output sections, then the privacy table.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push('\n');
+        out.push_str(&render_core_tools_table(&self.tools));
+        out.push_str("\n\n");
+        if !self.members.is_empty() {
+            out.push_str(&render_member_matrix(&self.members));
+            out.push_str("\n\n");
+        }
+        for m in self.feature_matrix.iter().chain(&self.toolchain_matrix) {
+            out.push_str(&render_matrix(m));
+            out.push_str("\n\n");
+        }
+        if self.run_timed_out {
+            out.push_str(
+                "Run timeout reached: running tools were cancelled, the rest not run.\n\n",
+            );
+        }
+        out.push_str(&build_privacy_security_table(&self.privacy).to_string());
+        out.push('\n');
+        out
+    }
+}
+
+/// `--only` / `--skip` filters over tool names and privacy scan kinds.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    pub only: Vec<String>,
+    pub skip: Vec<String>,
+}
+
+impl Selection {
+    /// Whether the tool or scan kind `name` should run.
+    pub fn allows(&self, name: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|n| n == name))
+            && !self.skip.iter().any(|n| n == name)
+    }
+
+    /// Details text for a row this selection filtered out.
+    pub fn skip_reason(&self, name: &str) -> String {
+        if self.skip.iter().any(|n| n == name) {
+            "skipped by --skip".to_string()
+        } else {
+            "not in --only".to_string()
+        }
+    }
+
+    /// Reject names that are neither a built-in/custom tool nor a scan kind.
+    pub fn validate(&self, cfg: &Config) -> Result<(), String> {
+        let mut known: Vec<&str> = vec!["rustfmt", "clippy", "cargo check", "cargo test"];
+        known.extend(cfg.tools.custom.iter().map(|c| c.name.as_str()));
+        known.extend(SCAN_KINDS);
+        for name in self.only.iter().chain(&self.skip) {
+            if !known.contains(&name.as_str()) {
+                return Err(format!(
+                    "unknown tool or scan kind `{name}`; expected one of: {}",
+                    known.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
 
 /// Run rustfmt, clippy, check, test, and the privacy/security scans.
-/// `run_extras` toggles the Extra scans row.
-/// Returns (all_ok, printable_blob).
-pub async fn run_checks(run_extras: bool) -> (bool, String) {
-    let (all_ok, tools_table) = run_core_tools_table().await;
-    let sec_table = build_privacy_security_table(run_extras);
-
-    let mut out = String::new();
-    out.push('\n');
-    out.push_str(&tools_table);
-    out.push_str("\n\n");
-    out.push_str(&sec_table.to_string());
-    out.push('\n');
-
-    (all_ok, out)
+/// `run_extras` toggles the Extra scans row; `fmt_mode` picks rustfmt write vs check;
+/// rows excluded by `selection` are reported as skipped.
+pub async fn run_checks(
+    cfg: &Config,
+    run_extras: bool,
+    fmt_mode: FmtMode,
+    selection: &Selection,
+) -> ChecksReport {
+    let (_never, cancel) = watch::channel(false);
+    run_checks_cancellable(cfg, run_extras, fmt_mode, selection, cancel).await
+}
+
+/// `run_checks` that stops early once `cancel` flips to true or
+/// `[tools].run_timeout_secs` passes: running tools are killed, the rest are
+/// skipped, and matrices are not started.
+pub async fn run_checks_cancellable(
+    cfg: &Config,
+    run_extras: bool,
+    fmt_mode: FmtMode,
+    selection: &Selection,
+    external: watch::Receiver<bool>,
+) -> ChecksReport {
+    let (deadline_tx, cancel) = watch::channel(false);
+    let run_timeout = cfg.tools.run_timeout_secs.map(Duration::from_secs);
+    let mut outer = external.clone();
+    let forward = tokio::spawn(async move {
+        let limit = async {
+            match run_timeout {
+                Some(limit) => tokio::time::sleep(limit).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = process::cancelled(&mut outer) => {}
+            _ = limit => {}
+        }
+        let _ = deadline_tx.send(true);
+    });
+    let ws = Workspace::discover().filter(|ws| cfg.tools.workspace && ws.is_multi());
+    let tools = run_core_tools(&cfg.tools, fmt_mode, selection, ws.is_some(), cancel.clone()).await;
+    let members = ws.as_ref().map(|ws| member_results(ws, &tools)).unwrap_or_default();
+    // Under fail-fast a failed main run already decided the outcome.
+    let stop =
+        (cfg.tools.on_failure == FailurePolicy::FailFast && !tools.all_ok()) || *cancel.borrow();
+    let feature_matrix = match cfg.matrix.features && !stop {
+        true => Some(run_feature_matrix(cfg, selection, ws.is_some(), &cancel).await),
+        false => None,
+    };
+    let toolchain_matrix = match !cfg.matrix.toolchains.is_empty() && !stop && !*cancel.borrow() {
+        true => Some(run_toolchain_matrix(cfg, selection, ws.is_some(), &cancel).await),
+        false => None,
+    };
+    forward.abort();
+    let run_timed_out = *cancel.borrow() && !*external.borrow();
+    let scan = collect_privacy_rows(cfg, run_extras, selection);
+    let scan_ok = scan.ok(run_extras);
+    let PrivacyScan { rows: privacy, baseline_loaded } = scan;
+    let mut report = ChecksReport {
+        ok: false,
+        tools,
+        members,
+        feature_matrix,
+        toolchain_matrix,
+        privacy,
+        baseline_loaded,
+        run_timed_out,
+    };
+    report.ok = report.tools_ok() && scan_ok;
+    report
 }
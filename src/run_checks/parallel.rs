@@ -0,0 +1,55 @@
+// Snippet
+// File: src/run_checks/parallel.rs
+
+use crate::config::FilesConfig;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// `items.iter().map(f)` spread over one thread per core. Items are handed
+/// out one at a time, so a few large files do not stall a whole chunk.
+/// Results keep `items` order.
+pub(super) fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let (f, next) = (&f, &next);
+    let mut out: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    std::thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            break done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect();
+        for w in workers {
+            for (i, r) in w.join().expect("scan worker panicked") {
+                out[i] = Some(r);
+            }
+        }
+    });
+    out.into_iter().map(|r| r.expect("every item is mapped")).collect()
+}
+
+/// Every file and directory under `.` that `[files]` does not skip, as
+/// `(path, is_file)` sorted by path. Top-level directories are walked in parallel.
+pub(super) fn discover(files_cfg: &FilesConfig) -> Vec<(PathBuf, bool)> {
+    let roots: Vec<PathBuf> = std::fs::read_dir(".")
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    let walked: Vec<Vec<(PathBuf, bool)>> = par_map(&roots, |root| {
+        files_cfg.walk(root).map(|e| (e.path().to_path_buf(), e.file_type().is_file())).collect()
+    });
+    let mut entries: Vec<(PathBuf, bool)> = walked.into_iter().flatten().collect();
+    entries.sort();
+    entries
+}
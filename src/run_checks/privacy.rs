@@ -1,118 +1,399 @@
 // Snippet
 // File: src/run_checks/privacy.rs
 
+use super::allow::Allowlist;
+use super::baseline::{fingerprint, Baseline, Remaining};
+use super::catalog::{Severity, RULES};
+use super::parallel::{discover, par_map};
+use super::scan_cache::{ruleset_hash, ScanCache};
+use super::scanner::LineScanner;
+use super::suppress::split_suppressed;
+use super::Selection;
+use crate::config::{Config, FilesConfig};
 use aho_corasick::AhoCorasickBuilder;
 use comfy_table::{presets::UTF8_FULL, Attribute, Cell, CellAlignment, Color, Table};
-use regex::Regex;
-use std::{collections::BTreeSet, env, fs, path::Path};
-use walkdir::WalkDir;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::OnceCell,
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Scan kinds accepted by `--only` / `--skip`: the candidate rows plus the Extra scans row.
+pub const SCAN_KINDS: &[&str] = &["username", "hostname", "ip-address", "extras"];
+
+/// Outcome of one privacy/security row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanStatus {
+    Found,
+    NotFound,
+    Skipped,
+    /// Nothing to scan for (no usernames/hostnames/IPs detected).
+    NotApplicable,
+    /// A summary row that reports no findings of its own (the Baseline row).
+    Info,
+}
+
+/// One match of one rule. `line`/`column` are 1-based (columns count chars);
+/// both are `None` for findings about a whole file (leak files).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule: String,
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub end_column: Option<usize>,
+    /// From the detector catalog; `None` for rules added via `[scan].patterns`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Extra context safe to show, e.g. a redacted token; never the secret itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Line-shift-proof identity used by the baseline; see `baseline::fingerprint`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub fingerprint: String,
+}
+
+/// One row of the privacy/security table.
+#[derive(Clone, Debug, Serialize)]
+pub struct PrivacyRow {
+    pub check: String,
+    pub value: String,
+    pub status: ScanStatus,
+    pub details: String,
+    /// `path:line,line,...` per file, or a bare path for leak files.
+    pub locations: Vec<String>,
+    pub findings: Vec<Finding>,
+    /// Matches silenced by `run_checks:allow(...)` comments; not counted as found.
+    pub suppressed: Vec<Finding>,
+    /// Matches recorded in the baseline file; not counted as found.
+    pub baselined: Vec<Finding>,
+}
+
+/// Rows of one privacy scan.
+pub struct PrivacyScan {
+    pub rows: Vec<PrivacyRow>,
+    /// Whether findings were compared against a `[scan].baseline` file.
+    pub baseline_loaded: bool,
+}
+
+impl PrivacyScan {
+    /// False when a row reports new findings (not suppressed or baselined)
+    /// and the scan is enforced: a baseline is loaded, or the Extra scans ran.
+    /// A plain `checks` run without a baseline only reports what it found.
+    pub fn ok(&self, run_extras: bool) -> bool {
+        let enforced = self.baseline_loaded || run_extras;
+        !enforced || self.rows.iter().all(|r| r.status != ScanStatus::Found)
+    }
+}
+
+/// Scan the project for local usernames/hostnames/IPs and, when `run_extras`
+/// is set, for secrets and leak-prone files. Kinds excluded by `selection`
+/// are reported as skipped rows. Unchanged files reuse cached findings
+/// unless `[scan].cache` is off; findings recorded in the `[scan].baseline`
+/// file are reported as baselined, followed by a Baseline row.
+pub fn collect_privacy_rows(cfg: &Config, run_extras: bool, selection: &Selection) -> PrivacyScan {
+    match Baseline::load(Path::new(&cfg.scan.baseline)) {
+        Ok(baseline) => PrivacyScan {
+            rows: collect_rows(cfg, run_extras, selection, baseline.as_ref()),
+            baseline_loaded: baseline.is_some(),
+        },
+        Err(e) => {
+            let mut rows = collect_rows(cfg, run_extras, selection, None);
+            rows.push(skipped_row("Baseline", &cfg.scan.baseline, e));
+            PrivacyScan { rows, baseline_loaded: false }
+        }
+    }
+}
+
+/// `collect_privacy_rows` against an already loaded baseline, if any.
+pub(super) fn collect_rows(
+    cfg: &Config,
+    run_extras: bool,
+    selection: &Selection,
+    baseline: Option<&Baseline>,
+) -> Vec<PrivacyRow> {
+    let mut ctx = ScanContext {
+        files: ProjectFiles {
+            files_cfg: &cfg.files,
+            entries: OnceCell::new(),
+            text_files: OnceCell::new(),
+        },
+        cache: ScanCache::load(cfg.scan.cache),
+        allow: Allowlist::new(&cfg.scan),
+        baseline: baseline.map(Remaining::new),
+    };
+    let mut rows = privacy_rows(cfg, run_extras, selection, &mut ctx);
+    if let Some(remaining) = &ctx.baseline {
+        let resolved = remaining.resolved(|rule| rule_scanned(rule, run_extras, selection));
+        let baselined: usize = rows.iter().map(|r| r.baselined.len()).sum();
+        rows.push(PrivacyRow {
+            check: "Baseline".to_string(),
+            value: cfg.scan.baseline.clone(),
+            status: ScanStatus::Info,
+            details: format!("{baselined} baselined, {} resolved", resolved.len()),
+            locations: resolved.iter().map(|e| format!("{} ({})", e.path, e.rule)).collect(),
+            findings: Vec::new(),
+            suppressed: Vec::new(),
+            baselined: Vec::new(),
+        });
+    }
+    ctx.cache.save();
+    rows
+}
+
+/// Whether findings of `rule` were looked for: scan kinds by name, every
+/// other rule through the Extra scans row.
+pub(super) fn rule_scanned(rule: &str, run_extras: bool, selection: &Selection) -> bool {
+    match SCAN_KINDS.contains(&rule) {
+        true => selection.allows(rule),
+        false => run_extras && selection.allows("extras"),
+    }
+}
+
+/// State shared by the rows of one scan.
+struct ScanContext<'a> {
+    files: ProjectFiles<'a>,
+    cache: ScanCache,
+    allow: Allowlist,
+    /// Baseline entries not matched yet, when a baseline file exists.
+    baseline: Option<Remaining>,
+}
+
+impl ScanContext<'_> {
+    /// Drop allowlisted findings, fingerprint the rest, then set aside
+    /// suppressed and baselined ones.
+    fn triage(&mut self, findings: Vec<Finding>) -> Triaged {
+        let findings = annotate(findings, self.files.text_files(), &self.allow);
+        let (found, suppressed) = split_suppressed(findings, self.files.text_files());
+        let (found, baselined) = match &mut self.baseline {
+            Some(remaining) => remaining.split(found),
+            None => (found, Vec::new()),
+        };
+        Triaged { found, suppressed, baselined }
+    }
+}
+
+/// A rule's findings sorted into what is reported and what is set aside.
+struct Triaged {
+    found: Vec<Finding>,
+    suppressed: Vec<Finding>,
+    baselined: Vec<Finding>,
+}
+
+/// Drop findings `allow` covers (by path, or by the matched text) and fill in
+/// `Finding::fingerprint` (from the text of the finding's line) and
+/// `Finding::severity` for the rest.
+fn annotate(findings: Vec<Finding>, files: &[(String, String)], allow: &Allowlist) -> Vec<Finding> {
+    let mut lines: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut kept = Vec::with_capacity(findings.len());
+    for mut f in findings {
+        if allow.allows_path(&f.rule, &f.path) {
+            continue;
+        }
+        let file_lines = lines.entry(f.path.clone()).or_insert_with(|| {
+            match files.binary_search_by(|(p, _)| Path::new(p).cmp(Path::new(&f.path))) {
+                Ok(i) => files[i].1.lines().collect(),
+                Err(_) => Vec::new(),
+            }
+        });
+        let line = f.line.and_then(|l| file_lines.get(l - 1).copied());
+        if let (Some(line), Some(start), Some(end)) = (line, f.column, f.end_column) {
+            let text: String = line.chars().skip(start - 1).take(end - start).collect();
+            if allow.allows_value(&f.rule, &text) {
+                continue;
+            }
+        }
+        f.fingerprint = fingerprint(&f.rule, &f.path, line);
+        f.severity = RULES.iter().find(|r| r.id == f.rule).map(|r| r.severity);
+        kept.push(f);
+    }
+    kept
+}
 
-pub fn build_privacy_security_table(run_extras: bool) -> Table {
+fn privacy_rows(
+    cfg: &Config,
+    run_extras: bool,
+    selection: &Selection,
+    ctx: &mut ScanContext,
+) -> Vec<PrivacyRow> {
     let usernames = gather_usernames();
     let hostnames = gather_hostnames();
     let ips = gather_ips();
 
-    let mut kinds: Vec<&'static str> = Vec::new();
+    let mut kinds: Vec<(&'static str, &'static str)> = Vec::new();
     let mut values: Vec<String> = Vec::new();
 
     for u in usernames {
-        kinds.push("Username");
+        kinds.push(("Username", "username"));
         values.push(u);
     }
     for h in hostnames {
-        kinds.push("Hostname");
+        kinds.push(("Hostname", "hostname"));
         values.push(h);
     }
     for ip in ips {
-        kinds.push("IP");
+        kinds.push(("IP", "ip-address"));
         values.push(ip);
     }
+    // Allowlisted values (e.g. `git`, `admin`) never become candidates.
+    let (kinds, values): (Vec<_>, Vec<_>) = kinds
+        .into_iter()
+        .zip(values)
+        .filter(|((_, rule), v)| !ctx.allow.allows_value(rule, v))
+        .unzip();
 
     if values.is_empty() {
-        let mut t = base_table();
-        t.add_row(vec![
-            Cell::new("Scan"),
-            Cell::new("No candidates"),
-            Cell::new("N/A").fg(Color::Yellow),
-            Cell::new("0"),
-            Cell::new(""),
-        ]);
-        add_extras_row(run_extras, &mut t);
-        return t;
+        return vec![
+            PrivacyRow {
+                check: "Scan".to_string(),
+                value: "No candidates".to_string(),
+                status: ScanStatus::NotApplicable,
+                details: "0".to_string(),
+                locations: Vec::new(),
+                findings: Vec::new(),
+                suppressed: Vec::new(),
+                baselined: Vec::new(),
+            },
+            extras_row(cfg, run_extras, selection, ctx),
+        ];
     }
+    let active: Vec<bool> = kinds.iter().map(|(_, rule)| selection.allows(rule)).collect();
 
     let ac = AhoCorasickBuilder::new()
         .ascii_case_insensitive(false)
         .build(&values)
         .expect("failed to build Aho-Corasick automaton");
 
-    let mut files_with_hits = vec![0usize; values.len()];
-    let mut total_hits = vec![0usize; values.len()];
-    let mut locations: Vec<Vec<String>> = vec![Vec::new(); values.len()];
-
-    for (path, content) in collect_project_text_files() {
-        let mut line_hits: Vec<Vec<usize>> = vec![Vec::new(); values.len()];
-        for (lineno0, line) in content.lines().enumerate() {
-            let lineno = lineno0 + 1;
-            let mut matched_on_line: Vec<usize> = Vec::new();
-
-            for m in ac.find_iter(line) {
-                let idx = m.pattern().as_usize();
-                total_hits[idx] += 1;
-                if matched_on_line.last().copied() != Some(idx) {
-                    matched_on_line.push(idx);
+    let mut findings: Vec<Vec<Finding>> = vec![Vec::new(); values.len()];
+
+    // Cache matches for every candidate so changing --only/--skip keeps the cache warm.
+    let ruleset: Vec<String> =
+        kinds.iter().zip(&values).map(|((_, rule), v)| format!("{rule}={v}")).collect();
+    let ruleset = ruleset_hash(&ruleset.iter().map(String::as_str).collect::<Vec<_>>());
+    let matches = match active.contains(&true) {
+        false => Vec::new(),
+        true => {
+            ctx.cache.scan_files("candidates", ruleset, ctx.files.text_files(), |path, content| {
+                let mut out: Vec<(usize, Finding)> = Vec::new();
+                for (lineno0, line) in content.lines().enumerate() {
+                    for m in ac.find_iter(line) {
+                        let idx = m.pattern().as_usize();
+                        out.push((
+                            idx,
+                            line_finding(kinds[idx].1, path, lineno0 + 1, line, m.span()),
+                        ));
+                    }
                 }
-            }
-            for idx in matched_on_line {
-                line_hits[idx].push(lineno);
-            }
+                out
+            })
         }
-
-        for (idx, lines) in line_hits.into_iter().enumerate() {
-            if !lines.is_empty() {
-                files_with_hits[idx] += 1;
-                let list = join_usize(&lines);
-                locations[idx].push(format!("{path}:{list}"));
-            }
+    };
+    for (idx, f) in matches {
+        if active[idx] {
+            findings[idx].push(f);
         }
     }
 
-    let mut t = base_table();
-    for i in 0..values.len() {
-        let found = total_hits[i] > 0;
-        let status = if found {
-            Cell::new("Found").add_attribute(Attribute::Bold).fg(Color::Red)
+    let mut rows = Vec::with_capacity(values.len() + 1);
+    for (i, found) in findings.into_iter().enumerate() {
+        if active[i] {
+            let triaged = ctx.triage(found);
+            rows.push(findings_row(kinds[i].0, &values[i], triaged, "hits"));
         } else {
-            Cell::new("Not found").add_attribute(Attribute::Bold).fg(Color::Green)
-        };
+            rows.push(skipped_row(kinds[i].0, &values[i], selection.skip_reason(kinds[i].1)));
+        }
+    }
 
-        let locs = if locations[i].is_empty() {
-            String::new()
-        } else if locations[i].len() <= 5 {
-            locations[i].join(" | ")
-        } else {
-            let shown = locations[i][..5].join(" | ");
-            format!("{shown} | +{} more files", locations[i].len() - 5)
-        };
+    rows.push(extras_row(cfg, run_extras, selection, ctx));
+    rows
+}
 
-        t.add_row(vec![
-            Cell::new(if i < kinds.len() { kinds[i] } else { "?" }),
-            Cell::new(&values[i]),
-            status,
-            Cell::new(if found {
-                format!("{} files, {} hits", files_with_hits[i], total_hits[i])
-            } else {
-                "not found".to_string()
-            }),
-            Cell::new(locs),
-        ]);
+/// Build a Found/Not found row from a rule's findings.
+fn findings_row(check: &str, value: &str, triaged: Triaged, noun: &str) -> PrivacyRow {
+    let Triaged { found: findings, suppressed, baselined } = triaged;
+    let found = !findings.is_empty();
+    let files: BTreeSet<&str> = findings.iter().map(|f| f.path.as_str()).collect();
+    let mut details = if found {
+        format!("{} files, {} {noun}", files.len(), findings.len())
+    } else {
+        "not found".to_string()
+    };
+    let mut aside = Vec::new();
+    if !suppressed.is_empty() {
+        aside.push(format!("{} suppressed", suppressed.len()));
+    }
+    if !baselined.is_empty() {
+        aside.push(format!("{} baselined", baselined.len()));
+    }
+    if !aside.is_empty() {
+        details.push_str(&format!(" ({})", aside.join(", ")));
+    }
+    PrivacyRow {
+        check: check.to_string(),
+        value: value.to_string(),
+        status: if found { ScanStatus::Found } else { ScanStatus::NotFound },
+        details,
+        locations: locations(&findings),
+        findings,
+        suppressed,
+        baselined,
     }
+}
 
-    add_extras_row(run_extras, &mut t);
-    t
+/// A finding for the byte range `span` of `line`, converted to char columns.
+pub(super) fn line_finding(
+    rule: &str,
+    path: &str,
+    lineno: usize,
+    line: &str,
+    span: impl Into<std::ops::Range<usize>>,
+) -> Finding {
+    let span = span.into();
+    let start = line[..span.start].chars().count() + 1;
+    let len = line[span.start..span.end].chars().count();
+    Finding {
+        rule: rule.to_string(),
+        path: path.to_string(),
+        line: Some(lineno),
+        column: Some(start),
+        end_column: Some(start + len),
+        severity: None,
+        detail: None,
+        fingerprint: String::new(),
+    }
+}
+
+/// Collapse findings into `path:line,line,...` (or bare `path`), in first-seen file order.
+fn locations(findings: &[Finding]) -> Vec<String> {
+    let mut order: Vec<&str> = Vec::new();
+    let mut lines: BTreeMap<&str, BTreeSet<usize>> = BTreeMap::new();
+    for f in findings {
+        let entry = lines.entry(f.path.as_str()).or_insert_with(|| {
+            order.push(f.path.as_str());
+            BTreeSet::new()
+        });
+        if let Some(l) = f.line {
+            entry.insert(l);
+        }
+    }
+    order
+        .into_iter()
+        .map(|p| {
+            let ls: Vec<usize> = lines[p].iter().copied().collect();
+            if ls.is_empty() {
+                p.to_string()
+            } else {
+                format!("{p}:{}", join_usize(&ls))
+            }
+        })
+        .collect()
 }
 
-fn base_table() -> Table {
+/// Render privacy rows as a table, collapsing long location lists.
+pub fn build_privacy_security_table(rows: &[PrivacyRow]) -> Table {
     let mut t = Table::new();
     t.load_preset(UTF8_FULL).set_header(vec![
         "Security/Privacy Check",
@@ -124,40 +405,66 @@ fn base_table() -> Table {
     if let Some(col) = t.column_mut(3) {
         col.set_cell_alignment(CellAlignment::Right);
     }
-    t
-}
 
-fn add_extras_row(run_extras: bool, t: &mut Table) {
-    if run_extras {
-        let (extra_found, extra_details, extra_locs) = run_extra_scans();
-        let status = if extra_found {
-            Cell::new("Found").add_attribute(Attribute::Bold).fg(Color::Red)
-        } else {
-            Cell::new("Not found").add_attribute(Attribute::Bold).fg(Color::Green)
+    for row in rows {
+        let status = match row.status {
+            ScanStatus::Found => Cell::new("Found").add_attribute(Attribute::Bold).fg(Color::Red),
+            ScanStatus::NotFound => {
+                Cell::new("Not found").add_attribute(Attribute::Bold).fg(Color::Green)
+            }
+            ScanStatus::Skipped => {
+                Cell::new("Skipped").add_attribute(Attribute::Bold).fg(Color::Yellow)
+            }
+            ScanStatus::NotApplicable => Cell::new("N/A").fg(Color::Yellow),
+            ScanStatus::Info => Cell::new("Info").fg(Color::Cyan),
         };
-        let locs = if extra_locs.is_empty() {
-            String::new()
-        } else if extra_locs.len() <= 5 {
-            extra_locs.join(" | ")
+        let locs = if row.locations.len() <= 5 {
+            row.locations.join(" | ")
         } else {
-            let shown = extra_locs[..5].join(" | ");
-            format!("{shown} | +{} more files", extra_locs.len() - 5)
+            let shown = row.locations[..5].join(" | ");
+            format!("{shown} | +{} more files", row.locations.len() - 5)
         };
         t.add_row(vec![
-            Cell::new("Extra scans"),
-            Cell::new("secrets, PEM, leak-files, docs/examples/tests"),
+            Cell::new(&row.check),
+            Cell::new(&row.value),
             status,
-            Cell::new(extra_details),
+            Cell::new(&row.details),
             Cell::new(locs),
         ]);
-    } else {
-        t.add_row(vec![
-            Cell::new("Extra scans"),
-            Cell::new("secrets, PEM, leak-files, docs/examples/tests"),
-            Cell::new("Skipped").add_attribute(Attribute::Bold).fg(Color::Yellow),
-            Cell::new("Run with: `run_checks checks-extras`"),
-            Cell::new(""),
-        ]);
+    }
+    t
+}
+
+fn extras_row(
+    cfg: &Config,
+    run_extras: bool,
+    selection: &Selection,
+    ctx: &mut ScanContext,
+) -> PrivacyRow {
+    const CHECK: &str = "Extra scans";
+    const VALUE: &str = "secrets, PEM, leak-files, docs/examples/tests";
+    if !selection.allows("extras") {
+        return skipped_row(CHECK, VALUE, selection.skip_reason("extras"));
+    }
+    if run_extras {
+        return match run_extra_scans(cfg, &ctx.files, &mut ctx.cache) {
+            Ok(findings) => findings_row(CHECK, VALUE, ctx.triage(findings), "findings"),
+            Err(e) => skipped_row(CHECK, VALUE, e),
+        };
+    }
+    skipped_row(CHECK, VALUE, "Run with: `run_checks checks-extras`".to_string())
+}
+
+fn skipped_row(check: &str, value: &str, details: String) -> PrivacyRow {
+    PrivacyRow {
+        check: check.to_string(),
+        value: value.to_string(),
+        status: ScanStatus::Skipped,
+        details,
+        locations: Vec::new(),
+        findings: Vec::new(),
+        suppressed: Vec::new(),
+        baselined: Vec::new(),
     }
 }
 
@@ -263,155 +570,120 @@ fn gather_ips() -> Vec<String> {
     set.into_iter().collect()
 }
 
-// Gather ALLOWED small text files in the repo.
-fn collect_project_text_files() -> Vec<(String, String)> {
-    const ALLOWED_EXTS: &[&str] = &["rs", "md", "sh", "toml"];
-    fn is_allowed_file(p: &Path) -> bool {
-        p.extension()
-            .and_then(|s| s.to_str())
-            .map(|ext| ALLOWED_EXTS.contains(&ext))
-            .unwrap_or(false)
+/// Project files, walked and read at most once per run and shared by the
+/// candidate scan and the Extra scans.
+struct ProjectFiles<'a> {
+    files_cfg: &'a FilesConfig,
+    entries: OnceCell<Vec<(PathBuf, bool)>>,
+    text_files: OnceCell<Vec<(String, String)>>,
+}
+
+impl ProjectFiles<'_> {
+    /// Every non-skipped file and directory, sorted.
+    fn entries(&self) -> &[(PathBuf, bool)] {
+        self.entries.get_or_init(|| discover(self.files_cfg))
     }
 
-    let mut files = Vec::new();
-    for entry in WalkDir::new(".")
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name == "node_modules" || p.is_symlink())
-        })
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-        if !is_allowed_file(path) {
-            continue;
-        }
-        if let Ok(md) = path.metadata() {
-            if md.len() > 1_000_000 {
-                continue;
-            }
-        }
-        if let Ok(content) = std::fs::read_to_string(path) {
-            if let Some(p) = path.to_str() {
-                files.push((p.to_string(), content));
-            }
-        }
+    /// Allowed text files with their contents, sorted by path.
+    fn text_files(&self) -> &[(String, String)] {
+        self.text_files.get_or_init(|| collect_project_text_files(self.files_cfg, self.entries()))
     }
-    files
 }
 
-fn run_extra_scans() -> (bool, String, Vec<String>) {
-    let re_words =
-        Regex::new(r"(?i)\b(api|secret|token|key|password|passwd|bearer|authorization)\b").unwrap();
-    let re_aws = Regex::new(r"AKIA[0-9A-Z]{16}").unwrap();
-    let re_gh = Regex::new(r"ghp_[A-Za-z0-9]{36,}").unwrap();
-    let re_slack = Regex::new(r"xox[baprs]-[A-Za-z0-9-]{10,}").unwrap();
-    let re_pem = Regex::new(r"BEGIN (RSA|DSA|EC|OPENSSH) (PRIVATE|PUBLIC) KEY").unwrap();
-    let re_pii =
-        Regex::new(r"(?i)(email|@example|phone|address|SIN|SSN|passport|license)").unwrap();
-
-    let text_files = collect_project_text_files();
-
-    let mut total_hits = 0usize;
-    let mut locs: Vec<String> = Vec::new();
-    let mut files_with_issues = BTreeSet::new();
-
-    for (path, content) in &text_files {
-        let mut line_nums: Vec<usize> = Vec::new();
-        for (i0, line) in content.lines().enumerate() {
-            let i = i0 + 1;
-            if re_words.is_match(line)
-                || re_aws.is_match(line)
-                || re_gh.is_match(line)
-                || re_slack.is_match(line)
-                || re_pem.is_match(line)
-            {
-                total_hits += 1;
-                if line_nums.last().copied() != Some(i) {
-                    line_nums.push(i);
-                }
-            }
-        }
-        if !line_nums.is_empty() {
-            files_with_issues.insert(path.clone());
-            locs.push(format!("{path}:{}", join_usize(&line_nums)));
+// Gather ALLOWED small text files among `entries`, read in parallel, in `entries` order.
+fn collect_project_text_files(
+    files_cfg: &FilesConfig,
+    entries: &[(PathBuf, bool)],
+) -> Vec<(String, String)> {
+    let candidates: Vec<&PathBuf> = entries
+        .iter()
+        .filter(|(path, is_file)| *is_file && files_cfg.is_allowed_file(path))
+        .map(|(path, _)| path)
+        .collect();
+    par_map(&candidates, |path| {
+        if path.metadata().is_ok_and(|md| md.len() > files_cfg.max_scan_bytes) {
+            return None;
         }
-    }
+        let content = std::fs::read_to_string(path).ok()?;
+        Some((path.to_str()?.to_string(), content))
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
 
-    for entry in WalkDir::new(".")
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name == "node_modules" || p.is_symlink())
-        })
-        .filter_map(Result::ok)
-    {
-        let p = entry.path();
-        let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-        let path_str = p.to_string_lossy();
-
-        let is_leak_file = name.starts_with(".env")
-            || name == ".envrc"
-            || name == "kubeconfig"
-            || name.starts_with("id_rsa")
-            || name.starts_with("id_ed25519")
-            || name.ends_with(".pem")
-            || name.ends_with(".p12")
-            || name.ends_with(".crt")
-            || name.ends_with(".key")
-            || path_str.ends_with(".kube/config")
-            || name == ".npmrc"
-            || name == ".pypirc"
-            || name == ".netrc"
-            || name == ".git-credentials"
-            || name == ".aws";
-
-        if is_leak_file {
-            files_with_issues.insert(path_str.to_string());
-            locs.push(path_str.to_string());
-            total_hits += 1;
-        }
-    }
+/// Errors only for `[scan].leak_files` globs that do not compile, which
+/// `Config::validate` already rejects when the config is loaded.
+fn run_extra_scans(
+    cfg: &Config,
+    files: &ProjectFiles,
+    cache: &mut ScanCache,
+) -> Result<Vec<Finding>, String> {
+    let scan = &cfg.scan;
+    let leak_globs = match scan.disabled_rules.iter().any(|d| d == "leak-file") {
+        true => None,
+        false => Some(scan.leak_file_globs()?),
+    };
 
-    for (path, content) in &text_files {
-        let lower = path.to_lowercase();
-        if !(lower.starts_with("docs/")
-            || lower.contains("/docs/")
-            || lower.starts_with("examples/")
-            || lower.contains("/examples/")
-            || lower.starts_with("tests/")
-            || lower.contains("/tests/"))
-        {
-            continue;
-        }
-        let mut line_nums: Vec<usize> = Vec::new();
-        for (i0, line) in content.lines().enumerate() {
-            let i = i0 + 1;
-            if re_pii.is_match(line) {
-                total_hits += 1;
-                if line_nums.last().copied() != Some(i) {
-                    line_nums.push(i);
-                }
+    let scanner = LineScanner::new(scan);
+    let scanned = cache.scan_files("extras", scanner.fingerprint(), files.text_files(), |p, c| {
+        scanner.scan_file(p, c)
+    });
+    // Report order: line rules, leak files, then PII.
+    let (pii, mut findings): (Vec<Finding>, Vec<Finding>) =
+        scanned.into_iter().partition(|f| f.rule == "pii");
+
+    if let Some((names, paths)) = &leak_globs {
+        for (p, _) in files.entries() {
+            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
+            let rel = p.strip_prefix(".").unwrap_or(p);
+            if names.is_match(name) || paths.is_match(rel) {
+                findings.push(Finding {
+                    rule: "leak-file".to_string(),
+                    path: p.to_string_lossy().to_string(),
+                    line: None,
+                    column: None,
+                    end_column: None,
+                    severity: None,
+                    detail: None,
+                    fingerprint: String::new(),
+                });
             }
         }
-        if !line_nums.is_empty() {
-            files_with_issues.insert(path.clone());
-            locs.push(format!("{path}:{}", join_usize(&line_nums)));
-        }
     }
 
-    let found = !files_with_issues.is_empty();
-    let details = if found {
-        format!("{} files, {} findings", files_with_issues.len(), total_hits)
-    } else {
-        "not found".to_string()
-    };
-    (found, details, locs)
+    findings.extend(pii);
+    Ok(findings)
 }
 
 fn join_usize(nums: &[usize]) -> String {
     nums.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding() -> Finding {
+        line_finding("secret-keyword", "./src/a.rs", 1, "password = x", 0..8)
+    }
+
+    fn scan(triaged: Triaged, baseline_loaded: bool) -> PrivacyScan {
+        let rows = vec![findings_row("Extra scans", "secrets", triaged, "findings")];
+        PrivacyScan { rows, baseline_loaded }
+    }
+
+    #[test]
+    fn baselined_finding_passes() {
+        let triaged = Triaged { found: vec![], suppressed: vec![], baselined: vec![finding()] };
+        assert!(scan(triaged, true).ok(true));
+    }
+
+    #[test]
+    fn new_finding_fails_when_enforced() {
+        let new = || Triaged { found: vec![finding()], suppressed: vec![], baselined: vec![] };
+        assert!(!scan(new(), true).ok(false));
+        assert!(!scan(new(), false).ok(true));
+        assert!(scan(new(), false).ok(false));
+    }
+}
@@ -0,0 +1,200 @@
+// Snippet
+// File: src/run_checks/process.rs
+
+use crate::util::strip_ansi_sgr;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::sync::watch;
+
+/// How long to wait for output pipes to close after killing a timed-out tree.
+const DRAIN_GRACE: Duration = Duration::from_secs(2);
+
+/// How a captured child ended.
+#[derive(Debug)]
+pub enum Exit {
+    Status(ExitStatus),
+    SpawnError(std::io::Error),
+    TimedOut,
+    /// Killed because the pipeline was cancelled.
+    Cancelled,
+}
+
+/// A finished (or killed) child with its output, ANSI escapes stripped.
+#[derive(Debug)]
+pub struct Captured {
+    pub exit: Exit,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed: Duration,
+}
+
+/// Spawn `argv` with stdout/stderr piped and wait for it. If `timeout` elapses
+/// or `cancel` flips to true first, the whole process tree is killed and
+/// whatever output was produced up to that point is returned with
+/// `Exit::TimedOut` or `Exit::Cancelled`.
+pub async fn run_captured(
+    argv: &[String],
+    cwd: Option<&Path>,
+    env: &BTreeMap<String, String>,
+    timeout: Option<Duration>,
+    mut cancel: watch::Receiver<bool>,
+) -> Captured {
+    let start = Instant::now();
+    let mut command = Command::new(&argv[0]);
+    command
+        .args(&argv[1..])
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = cwd {
+        command.current_dir(dir);
+    }
+    // Own process group, so the child and everything it starts die together.
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = match command.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            return Captured {
+                exit: Exit::SpawnError(e),
+                stdout: String::new(),
+                stderr: String::new(),
+                elapsed: start.elapsed(),
+            }
+        }
+    };
+    let group = child.id().map(LiveGroup::register);
+    let stdout = tokio::spawn(read_all(child.stdout.take()));
+    let stderr = tokio::spawn(read_all(child.stderr.take()));
+
+    let limit = async {
+        match timeout {
+            Some(limit) => tokio::time::sleep(limit).await,
+            None => std::future::pending().await,
+        }
+    };
+    let exit = tokio::select! {
+        status = child.wait() => status.map(Exit::Status).unwrap_or_else(Exit::SpawnError),
+        _ = limit => {
+            kill(&mut child).await;
+            Exit::TimedOut
+        }
+        _ = cancelled(&mut cancel) => {
+            kill(&mut child).await;
+            Exit::Cancelled
+        }
+    };
+    let elapsed = start.elapsed();
+    drop(group);
+
+    // A killed tree closes its pipes; a grandchild that escaped it must not hang us.
+    let drain = |h: tokio::task::JoinHandle<Vec<u8>>| async move {
+        match tokio::time::timeout(DRAIN_GRACE, h).await {
+            Ok(Ok(buf)) => strip_ansi_sgr(&String::from_utf8_lossy(&buf)),
+            _ => String::new(),
+        }
+    };
+    let (stdout, stderr) = tokio::join!(drain(stdout), drain(stderr));
+
+    Captured { exit, stdout, stderr, elapsed }
+}
+
+/// Resolve once `cancel` flips to true. A dropped sender means nobody can
+/// cancel any more, so this then never resolves.
+pub async fn cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|c| *c).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn kill(child: &mut tokio::process::Child) {
+    if let Some(pid) = child.id() {
+        kill_tree(pid);
+    }
+    let _ = child.kill().await;
+}
+
+async fn read_all<R: AsyncRead + Unpin>(pipe: Option<R>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(mut p) = pipe {
+        let _ = p.read_to_end(&mut buf).await;
+    }
+    buf
+}
+
+/// Kill `pid`'s process group: the child and all of its descendants, in one step.
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    // SAFETY: killpg only sends a signal; the group id came from our own child.
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_tree(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Process groups of running children. They are outside the terminal's
+/// foreground group and miss its Ctrl-C, so the first Ctrl-C kills them all
+/// and exits.
+static LIVE_GROUPS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Registration of one child's process group, removed on drop.
+struct LiveGroup(u32);
+
+impl LiveGroup {
+    fn register(pid: u32) -> LiveGroup {
+        static ON_INTERRUPT: Once = Once::new();
+        ON_INTERRUPT.call_once(|| {
+            tokio::spawn(async {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    let groups = LIVE_GROUPS.lock().unwrap_or_else(|e| e.into_inner());
+                    groups.iter().copied().for_each(kill_tree);
+                    std::process::exit(130);
+                }
+            });
+        });
+        LIVE_GROUPS.lock().unwrap_or_else(|e| e.into_inner()).insert(pid);
+        LiveGroup(pid)
+    }
+}
+
+impl Drop for LiveGroup {
+    fn drop(&mut self) {
+        LIVE_GROUPS.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.0);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn timeout_kills_grandchildren_holding_the_pipes() {
+        let argv: Vec<String> =
+            ["sh", "-c", "sleep 30 & echo started; wait"].iter().map(|s| s.to_string()).collect();
+        let (_tx, cancel) = watch::channel(false);
+        let started = Instant::now();
+        let c =
+            run_captured(&argv, None, &BTreeMap::new(), Some(Duration::from_millis(300)), cancel)
+                .await;
+        assert!(matches!(c.exit, Exit::TimedOut), "{:?}", c.exit);
+        assert_eq!(c.stdout.trim(), "started");
+        // Without the group kill, `sleep` keeps stdout open until DRAIN_GRACE.
+        assert!(started.elapsed() < DRAIN_GRACE, "{:?}", started.elapsed());
+    }
+}
@@ -1,66 +1,551 @@
 // Snippet
 // File: src/run_checks/run_tools.rs
 
+use super::diagnostics::{self, Diagnostic};
+use super::process::{self, Exit};
+use super::scheduler;
+use super::test_results::{self, TestSummary};
+use super::Selection;
+use crate::config::{CustomTool, ToolsConfig};
+use clap::ValueEnum;
 use comfy_table::{presets::UTF8_FULL, Attribute, Cell, CellAlignment, Color, Row, Table};
-use futures::future::join_all;
-use std::time::Instant;
-use tokio::process::Command;
-
-/// Execute core cargo tools and return (all_ok, table_string).
-pub async fn run_core_tools_table() -> (bool, String) {
-    async fn run_tool(name: &str, cmd: &[&str]) -> (String, bool, String) {
-        let start = Instant::now();
-        let ok = Command::new(cmd[0])
-            .args(&cmd[1..])
-            .status()
-            .await
-            .ok()
-            .map(|s| s.success())
-            .unwrap_or(false);
-        let elapsed = format!("{:.3} seconds", start.elapsed().as_secs_f64());
-        (name.to_string(), ok, elapsed)
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::IsTerminal as _;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// How the rustfmt row treats the working tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FmtMode {
+    /// `check` under CI or when stdout is not a terminal, `write` otherwise.
+    #[default]
+    Auto,
+    /// Run `cargo fmt --all -- --check`; fail if any file needs formatting.
+    Check,
+    /// Run `cargo fmt --all`; rewrites files in place.
+    Write,
+}
+
+impl FmtMode {
+    /// Collapse `Auto` into a concrete mode for this process.
+    fn resolve(self) -> FmtMode {
+        match self {
+            FmtMode::Auto => {
+                let ci = std::env::var_os("CI").is_some_and(|v| !v.is_empty() && v != "false");
+                if ci || !std::io::stdout().is_terminal() {
+                    FmtMode::Check
+                } else {
+                    FmtMode::Write
+                }
+            }
+            m => m,
+        }
+    }
+}
+
+/// Final state of one tool row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    Success,
+    Failed,
+    /// Killed after exceeding its timeout.
+    TimedOut,
+    /// Not run, e.g. because a dependency failed under `skip-dependents`.
+    Skipped,
+    /// Killed mid-run: another tool failed under `fail-fast`, or the run was cancelled.
+    Cancelled,
+}
+
+/// Outcome of one tool run, with its stdout/stderr captured separately.
+#[derive(Debug, Serialize)]
+pub struct ToolResult {
+    pub name: String,
+    pub command: Vec<String>,
+    pub status: ToolStatus,
+    /// A failure of this tool is reported as a warning and does not fail the run.
+    pub allow_failure: bool,
+    pub details: String,
+    /// Time spent running the command.
+    pub elapsed_secs: f64,
+    /// Time spent queued behind dependencies, the job limit or the build lock.
+    pub waited_secs: f64,
+    /// Part of `waited_secs` spent ready to run but blocked by the build lock.
+    pub lock_wait_secs: f64,
+    pub stdout: String,
+    pub stderr: String,
+    /// Parsed compiler messages, for tools run with `--message-format=json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<Diagnostic>>,
+    /// Parsed libtest results, for `cargo test`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestSummary>,
+}
+
+impl ToolResult {
+    /// True unless the tool failed or timed out; skipped and cancelled rows,
+    /// and failures of `allow_failure` tools, do not fail a run.
+    pub fn ok(&self) -> bool {
+        self.allow_failure || !self.failed()
+    }
+
+    /// Failed or timed out, whether or not that failure is allowed.
+    pub fn failed(&self) -> bool {
+        matches!(self.status, ToolStatus::Failed | ToolStatus::TimedOut)
+    }
+
+    /// Failed, but only as a warning.
+    pub fn warning(&self) -> bool {
+        self.allow_failure && self.failed()
+    }
+
+    pub(super) fn skipped(spec: &ToolSpec, details: String, waited_secs: f64) -> Self {
+        Self {
+            name: spec.name.clone(),
+            command: spec.argv.clone(),
+            status: ToolStatus::Skipped,
+            allow_failure: spec.allow_failure,
+            details,
+            elapsed_secs: 0.0,
+            waited_secs,
+            lock_wait_secs: 0.0,
+            stdout: String::new(),
+            stderr: String::new(),
+            diagnostics: None,
+            tests: None,
+        }
+    }
+}
+
+/// Results of one pass over the core tools.
+#[derive(Debug, Serialize)]
+pub struct ToolsRun {
+    pub results: Vec<ToolResult>,
+    pub elapsed_secs: f64,
+}
+
+impl ToolsRun {
+    pub fn all_ok(&self) -> bool {
+        self.results.iter().all(ToolResult::ok)
+    }
+}
+
+/// Everything needed to run one row: argv, process settings and scheduling constraints.
+pub(super) struct ToolSpec {
+    pub(super) name: String,
+    argv: Vec<String>,
+    cwd: Option<PathBuf>,
+    env: BTreeMap<String, String>,
+    ok_exit_codes: Vec<i32>,
+    timeout: Option<Duration>,
+    /// Rows that must finish first.
+    pub(super) after: Vec<String>,
+    /// Holds the shared `target/` lock while running.
+    pub(super) build_lock: bool,
+    allow_failure: bool,
+}
+
+impl ToolSpec {
+    fn builtin(cfg: &ToolsConfig, name: &str, fmt_mode: FmtMode, workspace: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            argv: builtin_command(cfg, name, fmt_mode, workspace),
+            cwd: None,
+            env: BTreeMap::new(),
+            ok_exit_codes: vec![0],
+            timeout: cfg.timeout_for(name, None).map(Duration::from_secs),
+            after: builtin_deps(name)
+                .iter()
+                .filter(|d| cfg.run.iter().any(|r| r == *d))
+                .map(|d| d.to_string())
+                .collect(),
+            build_lock: name != "rustfmt",
+            allow_failure: cfg.allow_failure.iter().any(|a| a == name),
+        }
     }
 
+    /// A compile step for a matrix cell, named `<tool> [<label>]`: the built-in
+    /// command run as `cargo +<toolchain>` if given, with its feature flags
+    /// replaced by `feature_args` if given.
+    pub(super) fn variant(
+        cfg: &ToolsConfig,
+        name: &str,
+        workspace: bool,
+        label: &str,
+        toolchain: Option<&str>,
+        feature_args: Option<&[String]>,
+    ) -> Self {
+        let mut spec = Self::builtin(cfg, name, FmtMode::Check, workspace);
+        spec.name = format!("{name} [{label}]");
+        if let Some(args) = feature_args {
+            spec.argv = with_feature_args(&spec.argv, args);
+        }
+        if let Some(tc) = toolchain {
+            spec.argv.insert(1, format!("+{tc}"));
+            // Never let rustup download a toolchain mid-run.
+            spec.env.insert("RUSTUP_AUTO_INSTALL".to_string(), "0".to_string());
+        }
+        spec.after.clear();
+        spec
+    }
+
+    fn custom(cfg: &ToolsConfig, c: &CustomTool) -> Self {
+        Self {
+            name: c.name.clone(),
+            argv: c.command.clone(),
+            cwd: c.cwd.clone(),
+            env: c.env.clone(),
+            ok_exit_codes: c.ok_exit_codes.clone(),
+            timeout: cfg.timeout_for(&c.name, c.timeout_secs).map(Duration::from_secs),
+            after: c.after.clone(),
+            build_lock: c.build_lock,
+            allow_failure: c.allow_failure,
+        }
+    }
+}
+
+/// Execute the configured built-in tools and `[[tools.custom]]` commands,
+/// respecting dependencies, the build lock, `[tools].jobs` and
+/// `[tools].on_failure`, and collect their results in tool order. Tools
+/// excluded by `selection` are not run and come back as skipped rows.
+/// `workspace` adds `--workspace` to the compile steps; flipping `cancel`
+/// kills running tools and skips the rest.
+pub async fn run_core_tools(
+    cfg: &ToolsConfig,
+    fmt_mode: FmtMode,
+    selection: &Selection,
+    workspace: bool,
+    cancel: watch::Receiver<bool>,
+) -> ToolsRun {
     let started = Instant::now();
-    let tools: &[(&str, &[&str])] = &[
-        ("rustfmt", &["cargo", "fmt", "--all"]),
-        ("clippy", &["cargo", "clippy", "--all-targets", "--all-features", "--", "-D", "warnings"]),
-        ("cargo check", &["cargo", "check"]),
-        ("cargo test", &["cargo", "test"]),
-    ];
+    let fmt_mode = fmt_mode.resolve();
+    let specs: Vec<ToolSpec> = cfg
+        .run
+        .iter()
+        .map(|name| ToolSpec::builtin(cfg, name, fmt_mode, workspace))
+        .chain(cfg.custom.iter().map(|c| ToolSpec::custom(cfg, c)))
+        .collect();
+
+    let selected: Vec<&ToolSpec> = specs.iter().filter(|s| selection.allows(&s.name)).collect();
+    let jobs = cfg.jobs.unwrap_or(selected.len()).max(1);
+    let mut ran =
+        scheduler::run_scheduled(&selected, jobs, cfg.on_failure, cancel).await.into_iter();
+    let mut results: Vec<ToolResult> = specs
+        .iter()
+        .map(|s| match selection.allows(&s.name) {
+            true => ran.next().expect("one result per scheduled tool"),
+            false => ToolResult::skipped(s, selection.skip_reason(&s.name), 0.0),
+        })
+        .collect();
+    if let Some(fmt) =
+        results.iter_mut().find(|r| r.name == "rustfmt" && r.status != ToolStatus::Skipped)
+    {
+        fmt.details = fmt_details(fmt_mode, fmt);
+    }
 
-    let results = join_all(tools.iter().map(|(n, c)| run_tool(n, c))).await;
+    ToolsRun { results, elapsed_secs: started.elapsed().as_secs_f64() }
+}
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL).set_header(vec!["Tool", "Status", "Time Elapsed"]);
-    if let Some(col) = table.column_mut(2) {
-        col.set_cell_alignment(CellAlignment::Right);
+/// Built-in ordering: formatting settles files before anything compiles them,
+/// and `cargo check` reports compile errors before `cargo test` rebuilds.
+fn builtin_deps(name: &str) -> &'static [&'static str] {
+    match name {
+        "clippy" | "cargo check" => &["rustfmt"],
+        "cargo test" => &["rustfmt", "cargo check"],
+        _ => &[],
+    }
+}
+
+pub(super) async fn run_tool(spec: &ToolSpec, cancel: watch::Receiver<bool>) -> ToolResult {
+    let cmd = &spec.argv;
+    let captured =
+        process::run_captured(cmd, spec.cwd.as_deref(), &spec.env, spec.timeout, cancel).await;
+    let mut result = ToolResult {
+        name: spec.name.clone(),
+        command: cmd.to_vec(),
+        status: ToolStatus::Failed,
+        allow_failure: spec.allow_failure,
+        details: String::new(),
+        elapsed_secs: captured.elapsed.as_secs_f64(),
+        waited_secs: 0.0,
+        lock_wait_secs: 0.0,
+        stdout: captured.stdout,
+        stderr: captured.stderr,
+        diagnostics: None,
+        tests: None,
+    };
+    // The cargo subcommand, skipping a `+toolchain` selector.
+    let subcommand = cmd.iter().skip(1).find(|a| !a.starts_with('+')).map(String::as_str);
+    let is_cargo = |sub: &str| cmd[0] == "cargo" && subcommand == Some(sub);
+    // Whether the parsers found anything worth showing instead of an exit code.
+    let mut parsed = false;
+    if cmd.iter().any(|a| a == "--message-format=json") {
+        let (diags, text) = diagnostics::parse_cargo_messages(&result.stdout);
+        result.details = diagnostics::summarize(&diags);
+        result.stdout = text;
+        parsed = !diags.is_empty();
+        result.diagnostics = Some(diags);
+    }
+    if is_cargo("test") {
+        let summary = test_results::parse_libtest(&result.stdout, &result.stderr);
+        result.details = test_results::summarize(&summary);
+        parsed = !summary.binaries.is_empty() || !summary.failures.is_empty();
+        result.tests = Some(summary);
+    }
+
+    match captured.exit {
+        Exit::Status(st) => match st.code() {
+            Some(code) if spec.ok_exit_codes.contains(&code) => result.status = ToolStatus::Success,
+            Some(_) if parsed => {}
+            Some(code) if spec.ok_exit_codes == [0] => result.details = format!("exit code {code}"),
+            Some(code) => {
+                result.details =
+                    format!("exit code {code} (expected {})", join_codes(&spec.ok_exit_codes));
+            }
+            None => result.details = "terminated by signal".to_string(),
+        },
+        Exit::SpawnError(e) => {
+            result.details = "failed to spawn".to_string();
+            result.stderr = format!("failed to spawn `{}`: {e}", cmd.join(" "));
+        }
+        Exit::TimedOut => {
+            result.status = ToolStatus::TimedOut;
+            let note = format!("timed out after {:.1}s", result.elapsed_secs);
+            result.details =
+                if parsed { format!("{note} (partial)\n{}", result.details) } else { note };
+        }
+        Exit::Cancelled => {
+            result.status = ToolStatus::Cancelled;
+            result.details = format!("cancelled after {:.1}s", result.elapsed_secs);
+        }
+    }
+    result
+}
+
+fn join_codes(codes: &[i32]) -> String {
+    codes.iter().map(|c| c.to_string()).collect::<Vec<_>>().join("/")
+}
+
+/// Replace any feature selection in a cargo argv (before `--`) with `feature_args`.
+fn with_feature_args(argv: &[String], feature_args: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(argv.len() + feature_args.len());
+    let mut args = argv.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--" => {
+                out.push(a.clone());
+                out.extend(args.by_ref().cloned());
+            }
+            "--all-features" | "--no-default-features" => {}
+            "--features" | "-F" => {
+                args.next();
+            }
+            _ if a.starts_with("--features=") => {}
+            _ => out.push(a.clone()),
+        }
     }
+    let at = out.len().min(2);
+    out.splice(at..at, feature_args.iter().cloned());
+    out
+}
 
-    let mut all_ok = true;
-    for (name, ok, elapsed) in &results {
-        if !ok {
-            all_ok = false;
+/// Full argv for a built-in tool, from `[tools]` settings.
+fn builtin_command(
+    cfg: &ToolsConfig,
+    name: &str,
+    fmt_mode: FmtMode,
+    workspace: bool,
+) -> Vec<String> {
+    let mut cmd: Vec<String> = Vec::new();
+    let mut push = |args: &[&str]| cmd.extend(args.iter().map(|a| a.to_string()));
+    match name {
+        "rustfmt" => match fmt_mode {
+            FmtMode::Write => push(&["cargo", "fmt", "--all"]),
+            _ => push(&["cargo", "fmt", "--all", "--", "--check"]),
+        },
+        "clippy" => {
+            push(&["cargo", "clippy"]);
+            cmd.extend(cfg.clippy_args.iter().cloned());
+            cmd.push("--message-format=json".to_string());
+            cmd.push("--".to_string());
+            cmd.extend(cfg.clippy_lints.iter().cloned());
+        }
+        "cargo check" => {
+            push(&["cargo", "check", "--message-format=json"]);
+            cmd.extend(cfg.check_args.iter().cloned());
         }
-        let status_cell = if *ok {
-            Cell::new("Success").add_attribute(Attribute::Bold).fg(Color::Green)
-        } else {
-            Cell::new("Failed").add_attribute(Attribute::Bold).fg(Color::Red)
+        "cargo test" => {
+            push(&["cargo", "test"]);
+            cmd.extend(cfg.test_args.iter().cloned());
+        }
+        _ => {}
+    }
+    // Respect an explicit package selection in the configured args.
+    let selects = |a: &String| a == "--workspace" || a == "-p" || a.starts_with("--package");
+    if workspace && name != "rustfmt" && !cmd.iter().any(selects) {
+        cmd.insert(2, "--workspace".to_string());
+    }
+    // cargo test stops at the first failing binary; the member matrix needs
+    // every member's results.
+    if workspace && name == "cargo test" && !cmd.iter().any(|a| a == "--no-fail-fast") {
+        cmd.insert(2, "--no-fail-fast".to_string());
+    }
+    cmd
+}
+
+/// Render the summary table followed by one output section per tool.
+pub fn render_core_tools_table(run: &ToolsRun) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header(vec![
+        "Tool",
+        "Status",
+        "Details",
+        "Waited",
+        "Lock wait",
+        "Time Elapsed",
+    ]);
+    for i in [3, 4, 5] {
+        if let Some(col) = table.column_mut(i) {
+            col.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+
+    for r in &run.results {
+        let status_cell = match r.status {
+            _ if r.warning() => {
+                Cell::new("Warning").add_attribute(Attribute::Bold).fg(Color::Yellow)
+            }
+            ToolStatus::Success => {
+                Cell::new("Success").add_attribute(Attribute::Bold).fg(Color::Green)
+            }
+            ToolStatus::Failed => Cell::new("Failed").add_attribute(Attribute::Bold).fg(Color::Red),
+            ToolStatus::TimedOut => {
+                Cell::new("Timed out").add_attribute(Attribute::Bold).fg(Color::Magenta)
+            }
+            ToolStatus::Skipped => {
+                Cell::new("Skipped").add_attribute(Attribute::Bold).fg(Color::Yellow)
+            }
+            ToolStatus::Cancelled => {
+                Cell::new("Cancelled").add_attribute(Attribute::Bold).fg(Color::Yellow)
+            }
         };
         table.add_row(vec![
-            Cell::new(name),
+            Cell::new(&r.name),
             status_cell,
-            Cell::new(elapsed).set_alignment(CellAlignment::Right),
+            Cell::new(&r.details),
+            Cell::new(format!("{:.3} seconds", r.waited_secs)).set_alignment(CellAlignment::Right),
+            Cell::new(format!("{:.3} seconds", r.lock_wait_secs))
+                .set_alignment(CellAlignment::Right),
+            Cell::new(format!("{:.3} seconds", r.elapsed_secs)).set_alignment(CellAlignment::Right),
         ]);
     }
 
     table.add_row(Row::from(vec![
         Cell::new("Total time elapsed:").add_attribute(Attribute::Bold),
         Cell::new(""),
-        Cell::new(format!("{:.3} seconds", started.elapsed().as_secs_f64()))
+        Cell::new(""),
+        Cell::new(""),
+        Cell::new(""),
+        Cell::new(format!("{:.3} seconds", run.elapsed_secs))
             .add_attribute(Attribute::Bold)
             .set_alignment(CellAlignment::Right),
     ]));
 
-    (all_ok, table.to_string())
+    let mut out = table.to_string();
+    for r in run.results.iter().filter(|r| r.status != ToolStatus::Skipped) {
+        out.push_str(&render_result_sections(r));
+    }
+    out
+}
+
+/// Output, diagnostics and test sections for one result, each preceded by a blank line.
+pub(super) fn render_result_sections(r: &ToolResult) -> String {
+    let mut out = String::new();
+    out.push_str("\n\n");
+    out.push_str(&render_output_section(r));
+    if let Some(diags) = &r.diagnostics {
+        out.push_str("\n\n");
+        out.push_str(&diagnostics::render_section(&r.name, diags));
+    }
+    if let Some(summary) = &r.tests {
+        out.push_str("\n\n");
+        out.push_str(&test_results::render_section(&r.name, summary));
+    }
+    out
+}
+
+/// Summarize the rustfmt row: in check mode, list the files rustfmt wants to change.
+fn fmt_details(mode: FmtMode, r: &ToolResult) -> String {
+    if mode == FmtMode::Write {
+        return if r.ok() { "formatted in place".to_string() } else { String::new() };
+    }
+    let files = unformatted_files(&r.stdout);
+    if files.is_empty() {
+        return if r.ok() { "all files formatted".to_string() } else { String::new() };
+    }
+    let shown = if files.len() <= 5 {
+        files.join(" | ")
+    } else {
+        format!("{} | +{} more files", files[..5].join(" | "), files.len() - 5)
+    };
+    format!("{} file(s) need formatting: {shown}", files.len())
+}
+
+/// Extract file paths from `rustfmt --check` output ("Diff in <path>:<line>:" or
+/// the older "Diff in <path> at line <n>:"), relative to the current dir when possible.
+pub(super) fn unformatted_files(stdout: &str) -> Vec<String> {
+    let cwd = std::env::current_dir().ok();
+    let mut files: Vec<String> = Vec::new();
+    for line in stdout.lines() {
+        let Some(rest) = line.strip_prefix("Diff in ") else {
+            continue;
+        };
+        let rest = rest.trim_end().trim_end_matches(':');
+        let path = match rest.split_once(" at line ") {
+            Some((p, _)) => p,
+            None => match rest.rsplit_once(':') {
+                Some((p, n)) if n.chars().all(|c| c.is_ascii_digit()) => p,
+                _ => rest,
+            },
+        };
+        let path = cwd
+            .as_deref()
+            .and_then(|c| std::path::Path::new(path).strip_prefix(c).ok())
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| path.to_string());
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Render one tool's captured stdout/stderr under a banner, in tool order.
+fn render_output_section(r: &ToolResult) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "{}\n{} output\n{}\n",
+        "========================================".green(),
+        r.name,
+        "========================================".green()
+    ));
+    let stdout = r.stdout.trim_end();
+    let stderr = r.stderr.trim_end();
+    if stdout.is_empty() && stderr.is_empty() {
+        out.push_str("(no output)");
+        return out;
+    }
+    if !stdout.is_empty() {
+        out.push_str(&format!("{}\n{stdout}", "--- stdout ---".cyan()));
+    }
+    if !stderr.is_empty() {
+        if !stdout.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("{}\n{stderr}", "--- stderr ---".cyan()));
+    }
+    out
 }
@@ -0,0 +1,135 @@
+// Snippet
+// File: src/run_checks/scan_cache.rs
+
+use super::parallel::par_map;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Bump when the scanners change what they report for the same input.
+const RULESET_VERSION: u32 = 1;
+const CACHE_FILE: &str = "target/run_checks/scan-cache.json";
+
+/// Per-file scan results kept between runs. Each scanner owns a section
+/// stamped with a fingerprint of its rules; a file's entry is reused only
+/// while both the fingerprint and the file's content hash are unchanged.
+pub(super) struct ScanCache {
+    path: Option<PathBuf>,
+    sections: BTreeMap<String, serde_json::Value>,
+    dirty: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    sections: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+struct Section<T> {
+    ruleset: u64,
+    files: BTreeMap<String, Entry<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+struct Entry<T> {
+    hash: u64,
+    results: Vec<T>,
+}
+
+impl ScanCache {
+    /// Read the cache file; a missing, unreadable or outdated file starts empty.
+    /// With `enabled` false nothing is read or written.
+    pub(super) fn load(enabled: bool) -> ScanCache {
+        let path = enabled.then(|| PathBuf::from(CACHE_FILE));
+        let sections = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str::<CacheFile>(&text).ok())
+            .filter(|f| f.version == RULESET_VERSION)
+            .map(|f| f.sections)
+            .unwrap_or_default();
+        ScanCache { path, sections, dirty: false }
+    }
+
+    /// Results of `scan(path, content)` for every file, in `files` order,
+    /// reusing cached results where possible and scanning the rest in
+    /// parallel. The section afterwards holds exactly these files, so deleted
+    /// files drop out.
+    pub(super) fn scan_files<T, F>(
+        &mut self,
+        section: &str,
+        ruleset: u64,
+        files: &[(String, String)],
+        scan: F,
+    ) -> Vec<T>
+    where
+        T: Clone + Send + Serialize + DeserializeOwned,
+        F: Fn(&str, &str) -> Vec<T> + Sync,
+    {
+        if self.path.is_none() {
+            return par_map(files, |(p, c)| scan(p, c)).into_iter().flatten().collect();
+        }
+        let mut old: BTreeMap<String, Entry<T>> = self
+            .sections
+            .remove(section)
+            .and_then(|v| serde_json::from_value::<Section<T>>(v).ok())
+            .filter(|s| s.ruleset == ruleset)
+            .map(|s| s.files)
+            .unwrap_or_default();
+        let hashes = par_map(files, |(_, c)| fnv1a(c.as_bytes()));
+        let cached: Vec<Option<Vec<T>>> = files
+            .iter()
+            .zip(&hashes)
+            .map(|((path, _), &hash)| {
+                old.remove(path).filter(|e| e.hash == hash).map(|e| e.results)
+            })
+            .collect();
+        let misses: Vec<usize> = (0..files.len()).filter(|&i| cached[i].is_none()).collect();
+        self.dirty |= !misses.is_empty();
+        let mut fresh = par_map(&misses, |&i| scan(&files[i].0, &files[i].1)).into_iter();
+
+        let mut new = BTreeMap::new();
+        let mut out = Vec::new();
+        for (((path, _), hash), hit) in files.iter().zip(hashes).zip(cached) {
+            let results = hit.or_else(|| fresh.next()).unwrap_or_default();
+            out.extend(results.iter().cloned());
+            new.insert(path.clone(), Entry { hash, results });
+        }
+        self.dirty |= !old.is_empty();
+        let section_value = Section { ruleset, files: new };
+        if let Ok(v) = serde_json::to_value(section_value) {
+            self.sections.insert(section.to_string(), v);
+        }
+        out
+    }
+
+    /// Write the cache back if anything changed. Failures are ignored: the
+    /// cache only saves time.
+    pub(super) fn save(self) {
+        let Some(path) = self.path.filter(|_| self.dirty) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let file = CacheFile { version: RULESET_VERSION, sections: self.sections };
+        if let Ok(text) = serde_json::to_string(&file) {
+            let _ = std::fs::write(path, text);
+        }
+    }
+}
+
+/// Fingerprint of the inputs that decide a scanner's results, e.g. its
+/// patterns and candidate values.
+pub(super) fn ruleset_hash(parts: &[&str]) -> u64 {
+    fnv1a(parts.join("\0").as_bytes())
+}
+
+/// 64-bit FNV-1a: stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
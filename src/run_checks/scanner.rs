@@ -0,0 +1,172 @@
+// Snippet
+// File: src/run_checks/scanner.rs
+
+use super::catalog::{NEAR_LINES, RULES};
+use super::entropy;
+use super::privacy::{line_finding, Finding};
+use super::scan_cache::ruleset_hash;
+use crate::config::{EntropyConfig, ScanConfig};
+use regex::{Regex, RegexSet};
+use std::path::Path;
+
+/// The Extra scans line rules (catalog detectors, `[scan].patterns` and `pii`) compiled into one
+/// `RegexSet`. Each line is matched against the set once; only the rules it
+/// reports are re-run to locate their matches, so clean lines cost a single
+/// pass however many rules there are. The entropy rule runs after the set.
+pub(super) struct LineScanner {
+    set: RegexSet,
+    /// Line rules in `set` order.
+    rules: Vec<LineRule>,
+    /// Index of the `pii` rule, which only applies under `pii_dirs`.
+    pii: Option<usize>,
+    pii_dirs: Vec<String>,
+    /// `None` when `high-entropy-string` is disabled.
+    entropy: Option<EntropyConfig>,
+}
+
+struct LineRule {
+    id: String,
+    re: Regex,
+    /// Context that must appear nearby, see `catalog::Rule::near`.
+    near: Option<Regex>,
+}
+
+impl LineScanner {
+    /// Compile the enabled rules: catalog line rules in catalog order (a
+    /// `[scan].patterns` entry with the same id replaces the pattern and its
+    /// `near` context), then the remaining `[scan].patterns` ids. Patterns
+    /// that do not compile are dropped, as in `Allowlist::new`.
+    pub(super) fn new(scan: &ScanConfig) -> LineScanner {
+        let enabled = |rule: &str| !scan.disabled_rules.iter().any(|d| d == rule);
+        let builtin = RULES.iter().filter_map(|r| {
+            let (pattern, near) = match scan.patterns.get(r.id) {
+                Some(p) => (p.as_str(), None),
+                None => (r.pattern?, r.near),
+            };
+            Some((r.id, pattern, near))
+        });
+        let added = scan
+            .patterns
+            .iter()
+            .filter(|(id, _)| !RULES.iter().any(|r| r.id == id.as_str()))
+            .map(|(id, p)| (id.as_str(), p.as_str(), None));
+        let mut rules: Vec<LineRule> = builtin
+            .chain(added)
+            .filter(|(id, _, _)| enabled(id))
+            .filter_map(|(id, pattern, near)| {
+                Some(LineRule {
+                    id: id.to_string(),
+                    re: Regex::new(pattern).ok()?,
+                    near: near.and_then(|n| Regex::new(n).ok()),
+                })
+            })
+            .collect();
+        let mut pii = None;
+        if let Some(re) = Regex::new(&scan.pii_pattern).ok().filter(|_| enabled("pii")) {
+            pii = Some(rules.len());
+            rules.push(LineRule { id: "pii".to_string(), re, near: None });
+        }
+        let set = RegexSet::new(rules.iter().map(|r| r.re.as_str()))
+            .unwrap_or_else(|_| RegexSet::empty());
+        let pii_dirs = scan.pii_dirs.iter().map(|d| d.to_lowercase()).collect();
+        let entropy = enabled(entropy::RULE).then(|| scan.entropy.clone());
+        LineScanner { set, rules, pii, pii_dirs, entropy }
+    }
+
+    /// Cache fingerprint: every rule id, pattern and context, the PII
+    /// directories and the entropy thresholds.
+    pub(super) fn fingerprint(&self) -> u64 {
+        let mut parts: Vec<&str> = Vec::new();
+        for r in &self.rules {
+            parts.extend([r.id.as_str(), r.re.as_str()]);
+            parts.extend(r.near.as_ref().map(Regex::as_str));
+        }
+        parts.extend(self.pii_dirs.iter().map(String::as_str));
+        let entropy = format!("{:?}", self.entropy);
+        parts.push(&entropy);
+        ruleset_hash(&parts)
+    }
+
+    /// Every match of every rule in `content`, by line, then rule order.
+    pub(super) fn scan_file(&self, path: &str, content: &str) -> Vec<Finding> {
+        let pii_applies = self.pii.is_some() && self.in_pii_dir(path);
+        let lines: Vec<&str> = content.lines().collect();
+        let mut out = Vec::new();
+        for (i0, &line) in lines.iter().enumerate() {
+            // Whole rule matches on this line; the entropy rule skips tokens
+            // inside them so a key is reported once, by the more specific rule.
+            let mut flagged = Vec::new();
+            for idx in self.set.matches(line).iter() {
+                if Some(idx) == self.pii && !pii_applies {
+                    continue;
+                }
+                let rule = &self.rules[idx];
+                if let Some(near) = &rule.near {
+                    let context = &lines
+                        [i0.saturating_sub(NEAR_LINES)..(i0 + NEAR_LINES + 1).min(lines.len())];
+                    if !context.iter().any(|l| near.is_match(l)) {
+                        continue;
+                    }
+                }
+                for caps in rule.re.captures_iter(line) {
+                    let m = caps.get(1).or_else(|| caps.get(0)).expect("group 0 always matches");
+                    out.push(line_finding(&rule.id, path, i0 + 1, line, m.range()));
+                    flagged.push(caps.get(0).expect("group 0 always matches").range());
+                }
+            }
+            if let Some(cfg) = &self.entropy {
+                for hit in entropy::scan_line(cfg, line) {
+                    if flagged.iter().any(|r| r.start < hit.range.end && hit.range.start < r.end) {
+                        continue;
+                    }
+                    let mut f = line_finding(entropy::RULE, path, i0 + 1, line, hit.range);
+                    f.detail = Some(hit.redacted);
+                    out.push(f);
+                }
+            }
+        }
+        out
+    }
+
+    fn in_pii_dir(&self, path: &str) -> bool {
+        Path::new(path).components().any(|c| {
+            let c = c.as_os_str().to_string_lossy().to_lowercase();
+            self.pii_dirs.contains(&c)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_checks::parallel::par_map;
+
+    #[test]
+    fn parallel_scan_matches_sequential_scan() {
+        let scanner = LineScanner::new(&ScanConfig::default());
+        let files: Vec<(String, String)> = (0..64)
+            .map(|i| {
+                // Assembled at run time so this file does not trip the scanner.
+                let id = format!("ghp_{}", "q7Zp2Lx9Vc4B".repeat(3 + i % 3));
+                let body = format!("fn f{i}() {{}}\nlet value = \"{id}\";\nemail me\n");
+                let dir = if i % 2 == 0 { "docs" } else { "src" };
+                (format!("./{dir}/f{i}.rs"), body)
+            })
+            .collect();
+        let sequential: Vec<Vec<Finding>> =
+            files.iter().map(|(p, c)| scanner.scan_file(p, c)).collect();
+        let parallel = par_map(&files, |(p, c)| scanner.scan_file(p, c));
+        assert_eq!(parallel, sequential);
+        assert!(sequential.iter().all(|f| !f.is_empty()));
+    }
+
+    #[test]
+    fn entropy_skips_tokens_a_catalog_rule_flagged() {
+        let scanner = LineScanner::new(&ScanConfig::default());
+        // Assembled at run time so this file does not trip the scanner.
+        let token = format!("{}_{}{}", "ghp", "aB3dE5gH7jK9mN1pQ2r", "StUvWxYz0123456789");
+        let findings = scanner.scan_file("./src/a.rs", &format!("let t = \"{token}\";\n"));
+        let rules: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+        assert_eq!(rules, ["github-token"]);
+    }
+}
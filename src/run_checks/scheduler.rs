@@ -0,0 +1,121 @@
+// Snippet
+// File: src/run_checks/scheduler.rs
+
+use super::process;
+use super::run_tools::{run_tool, ToolResult, ToolSpec, ToolStatus};
+use crate::config::FailurePolicy;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Run `specs` with at most `jobs` at a time. A row starts once everything in
+/// its `after` list has finished and, if it needs the build lock, no other
+/// lock holder is running. `policy` decides what a failure does to the rows
+/// after it (allowed failures never count). Flipping `external` stops the
+/// whole run as if by fail-fast. Results come back in `specs` order.
+pub(super) async fn run_scheduled(
+    specs: &[&ToolSpec],
+    jobs: usize,
+    policy: FailurePolicy,
+    mut external: watch::Receiver<bool>,
+) -> Vec<ToolResult> {
+    let started = Instant::now();
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    // Details for rows never started, set once the run is being stopped.
+    let mut stopped_by: Option<String> = None;
+    let deps: Vec<Vec<usize>> = specs
+        .iter()
+        .map(|s| s.after.iter().filter_map(|d| specs.iter().position(|o| &o.name == d)).collect())
+        .collect();
+    let mut results: Vec<Option<ToolResult>> = specs.iter().map(|_| None).collect();
+    let mut launched = vec![false; specs.len()];
+    let mut lock_held = false;
+    // Per row: since when it has been ready but blocked by the build lock, and
+    // how long that has added up to.
+    let mut lock_blocked_since: Vec<Option<Instant>> = vec![None; specs.len()];
+    let mut lock_wait = vec![Duration::ZERO; specs.len()];
+    let mut running = FuturesUnordered::new();
+
+    loop {
+        // Skipping a row can unblock its own dependents, so sweep until stable.
+        let mut progressed = true;
+        while progressed {
+            progressed = false;
+            for (i, spec) in specs.iter().enumerate() {
+                if launched[i] {
+                    continue;
+                }
+                if let Some(details) = &stopped_by {
+                    let waited = started.elapsed().as_secs_f64();
+                    results[i] = Some(ToolResult::skipped(spec, details.clone(), waited));
+                    launched[i] = true;
+                    continue;
+                }
+                if deps[i].iter().any(|&d| results[d].is_none()) {
+                    continue;
+                }
+                let blocker = deps[i]
+                    .iter()
+                    .filter_map(|&d| results[d].as_ref())
+                    .find(|r| !r.ok() || r.status == ToolStatus::Skipped);
+                if let (FailurePolicy::SkipDependents, Some(dep)) = (policy, blocker) {
+                    let details = format!("{} did not succeed", dep.name);
+                    let waited = started.elapsed().as_secs_f64();
+                    results[i] = Some(ToolResult::skipped(spec, details, waited));
+                    launched[i] = true;
+                    progressed = true;
+                    continue;
+                }
+                if spec.build_lock && lock_held {
+                    lock_blocked_since[i].get_or_insert_with(Instant::now);
+                    continue;
+                }
+                if let Some(since) = lock_blocked_since[i].take() {
+                    lock_wait[i] += since.elapsed();
+                }
+                if running.len() >= jobs {
+                    continue;
+                }
+                launched[i] = true;
+                lock_held |= spec.build_lock;
+                let waited = started.elapsed().as_secs_f64();
+                let lock_waited = lock_wait[i].as_secs_f64();
+                let cancel = cancel_rx.clone();
+                running.push(async move {
+                    let mut r = run_tool(spec, cancel).await;
+                    r.waited_secs = waited;
+                    r.lock_wait_secs = lock_waited;
+                    (i, r)
+                });
+            }
+        }
+        let next = tokio::select! {
+            next = running.next() => next,
+            _ = process::cancelled(&mut external), if stopped_by.is_none() => {
+                stopped_by = Some("not run: cancelled".to_string());
+                let _ = cancel_tx.send(true);
+                continue;
+            }
+        };
+        let Some((i, r)) = next else {
+            break;
+        };
+        if specs[i].build_lock {
+            lock_held = false;
+        }
+        if policy == FailurePolicy::FailFast && !r.ok() && stopped_by.is_none() {
+            stopped_by = Some(format!("not run: {} failed (fail-fast)", r.name));
+            let _ = cancel_tx.send(true);
+        }
+        results[i] = Some(r);
+    }
+
+    // Config validation rejects cycles, so every row has a result by now.
+    results
+        .into_iter()
+        .zip(specs)
+        .map(|(r, spec)| {
+            r.unwrap_or_else(|| ToolResult::skipped(spec, "dependency cycle".to_string(), 0.0))
+        })
+        .collect()
+}
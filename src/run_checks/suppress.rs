@@ -0,0 +1,259 @@
+// Snippet
+// File: src/run_checks/suppress.rs
+
+use super::privacy::Finding;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+const MARKER: &str = "run_checks:allow";
+
+/// Inline suppressions in one file:
+/// `run_checks:allow(rule-id, ...)` covers its own line and the next one;
+/// `run_checks:allow-file(rule-id, ...)` covers the whole file.
+/// Markers only count inside a comment of the file's language (see `Syntax`),
+/// and never inside a fenced code block of a Markdown file, so documentation
+/// examples and URLs or strings that merely contain a marker silence nothing.
+#[derive(Default)]
+struct Directives {
+    file: BTreeSet<String>,
+    /// 1-based line number -> rule ids allowed on it.
+    lines: BTreeMap<usize, BTreeSet<String>>,
+}
+
+impl Directives {
+    fn parse(content: &str, syntax: &Syntax) -> Directives {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| {
+            Regex::new(r"run_checks:allow(-file)?\(\s*([A-Za-z0-9_,\s-]+)\)").expect("valid regex")
+        });
+        let mut d = Directives::default();
+        // Open Markdown fence: its character and length.
+        let mut fence: Option<(char, usize)> = None;
+        for (i0, line) in content.lines().enumerate() {
+            if syntax.markdown {
+                let t = line.trim_start();
+                let ch = t.chars().next().unwrap_or(' ');
+                let len = t.chars().take_while(|&c| c == ch).count();
+                let is_fence = (ch == '`' || ch == '~') && len >= 3;
+                match fence {
+                    // A closing fence uses the same character, is at least as
+                    // long and has no info string.
+                    Some((open, open_len))
+                        if is_fence
+                            && ch == open
+                            && len >= open_len
+                            && t[len..].trim().is_empty() =>
+                    {
+                        fence = None;
+                    }
+                    Some(_) => {}
+                    None if is_fence => fence = Some((ch, len)),
+                    None => {}
+                }
+                if is_fence || fence.is_some() {
+                    continue;
+                }
+            }
+            if !line.contains(MARKER) {
+                continue;
+            }
+            let Some(comment) = syntax.comment_start(line) else {
+                continue;
+            };
+            for caps in re.captures_iter(line) {
+                if caps.get(0).map_or(0, |m| m.start()) < comment {
+                    continue;
+                }
+                let ids =
+                    caps[2].split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+                if caps.get(1).is_some() {
+                    d.file.extend(ids);
+                } else {
+                    for id in ids {
+                        for l in [i0 + 1, i0 + 2] {
+                            d.lines.entry(l).or_default().insert(id.clone());
+                        }
+                    }
+                }
+            }
+        }
+        d
+    }
+
+    fn allows(&self, f: &Finding) -> bool {
+        self.file.contains(&f.rule)
+            || f.line.and_then(|l| self.lines.get(&l)).is_some_and(|ids| ids.contains(&f.rule))
+    }
+}
+
+/// Comment and string syntax of a file, picked from its extension.
+struct Syntax {
+    /// Openers of comments that can hold a marker on the same line.
+    comments: &'static [&'static str],
+    /// Characters that open and close string literals.
+    quotes: &'static [char],
+    /// `'x'` / `'\x'` are character literals (C family), not string openers.
+    char_literals: bool,
+    /// Markdown: fenced code blocks are skipped as a whole.
+    markdown: bool,
+}
+
+impl Syntax {
+    fn for_path(path: &str) -> Syntax {
+        let ext =
+            Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        let (comments, quotes, char_literals): (&[&str], &[char], bool) = match ext.as_str() {
+            "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "go" | "java" | "kt" | "swift" | "cs"
+            | "scala" => (&["//", "/*"], &['"'], true),
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "jsonc" => {
+                (&["//", "/*"], &['"', '\'', '`'], false)
+            }
+            "sh" | "bash" | "zsh" | "toml" | "py" | "rb" | "pl" | "yaml" | "yml" | "env"
+            | "cfg" | "conf" | "ini" | "mk" | "dockerfile" => (&["#"], &['"', '\''], false),
+            "md" | "markdown" | "html" | "htm" | "xml" | "svg" => (&["<!--"], &[], false),
+            "sql" | "lua" | "hs" => (&["--", "/*"], &['"', '\''], false),
+            _ => (&["//", "/*", "#", "<!--", "--"], &['"'], false),
+        };
+        let markdown = ext == "md" || ext == "markdown";
+        Syntax { comments, quotes, char_literals, markdown }
+    }
+
+    /// Byte offset of the first comment opener in `line` outside a string
+    /// literal, or `None` if the line has no comment.
+    fn comment_start(&self, line: &str) -> Option<usize> {
+        let mut quote: Option<char> = None;
+        let mut chars = line.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match quote {
+                Some(_) if c == '\\' => {
+                    chars.next();
+                }
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if self.char_literals && c == '\'' => {
+                    // Skip `'x'` and `'\x'` whole; a lone `'` is a lifetime.
+                    let rest = &line[i + 1..];
+                    let len = if rest.starts_with('\\') { 3 } else { 2 };
+                    if rest.chars().nth(len - 1) == Some('\'') {
+                        for _ in 0..len {
+                            chars.next();
+                        }
+                    }
+                }
+                None if self.quotes.contains(&c) => quote = Some(c),
+                None if self.comments.iter().any(|o| line[i..].starts_with(o)) => return Some(i),
+                None => {}
+            }
+        }
+        None
+    }
+}
+
+/// Split findings into (reported, suppressed) using the directives in each
+/// finding's file. `files` is sorted by path; findings in files that were not
+/// read (e.g. leak files outside `[files].extensions`) are always reported.
+pub(super) fn split_suppressed(
+    findings: Vec<Finding>,
+    files: &[(String, String)],
+) -> (Vec<Finding>, Vec<Finding>) {
+    let mut parsed: BTreeMap<String, Directives> = BTreeMap::new();
+    findings.into_iter().partition(|f| {
+        let d = parsed.entry(f.path.clone()).or_insert_with(|| {
+            match files.binary_search_by(|(p, _)| Path::new(p).cmp(Path::new(&f.path))) {
+                Ok(i) if files[i].1.contains(MARKER) => {
+                    Directives::parse(&files[i].1, &Syntax::for_path(&f.path))
+                }
+                _ => Directives::default(),
+            }
+        });
+        !d.allows(f)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(path: &str, content: &str) -> Directives {
+        Directives::parse(content, &Syntax::for_path(path))
+    }
+
+    fn allowed(d: &Directives, rule: &str, line: usize) -> bool {
+        d.allows(&Finding {
+            rule: rule.to_string(),
+            path: "src/lib.rs".to_string(),
+            line: Some(line),
+            column: None,
+            end_column: None,
+            severity: None,
+            detail: None,
+            fingerprint: String::new(),
+        })
+    }
+
+    #[test]
+    fn line_directive_covers_own_and_next_line() {
+        let d = parse("src/lib.rs", "a\n// run_checks:allow(secret-keyword, pii)\nb\nc\n");
+        assert!(allowed(&d, "secret-keyword", 2));
+        assert!(allowed(&d, "pii", 3));
+        assert!(!allowed(&d, "secret-keyword", 4));
+        assert!(!allowed(&d, "jwt", 3));
+        assert!(d.file.is_empty());
+    }
+
+    #[test]
+    fn file_directive_covers_every_line() {
+        let d = parse("run.toml", "x = 1  # run_checks:allow-file(secret-keyword)\n");
+        assert!(allowed(&d, "secret-keyword", 40));
+        assert!(!allowed(&d, "pii", 40));
+    }
+
+    #[test]
+    fn marker_outside_comment_is_ignored() {
+        let d = parse("src/lib.rs", "let s = \"run_checks:allow-file(secret-keyword)\";\n");
+        assert!(d.file.is_empty());
+        assert!(d.lines.is_empty());
+    }
+
+    #[test]
+    fn markdown_fences_are_ignored() {
+        let md = "````markdown\n```rust\n// run_checks:allow-file(secret-keyword)\n```\n\
+                  // run_checks:allow(jwt)\n````\n<!-- run_checks:allow(pii) -->\n";
+        let d = parse("README.md", md);
+        assert!(d.file.is_empty());
+        assert!(!allowed(&d, "jwt", 5));
+        assert!(allowed(&d, "pii", 7));
+        assert!(!allowed(&d, "pii", 5));
+    }
+
+    #[test]
+    fn placeholder_ids_do_not_match() {
+        let d = parse("src/lib.rs", "// run_checks:allow-file(<rule-id>)\n");
+        assert!(d.file.is_empty());
+    }
+
+    #[test]
+    fn markers_inside_strings_are_ignored() {
+        let src = "let u = \"https://x.example/#run_checks:allow(high-entropy-string)\";\n\
+                   let a = \"// run_checks:allow(pii)\"; let b = '\"';\n\
+                   key = \"v\"\n";
+        for path in ["src/lib.rs", "src/app.ts", "run.toml"] {
+            let d = parse(path, src);
+            assert!(d.lines.is_empty(), "{path}");
+        }
+    }
+
+    #[test]
+    fn only_the_languages_comment_openers_count() {
+        let d = parse("src/lib.rs", "let r = x # run_checks:allow(pii)\n");
+        assert!(d.lines.is_empty());
+        let d = parse("run.sh", "curl http://h // run_checks:allow(pii)\n");
+        assert!(d.lines.is_empty());
+        let d = parse("src/lib.rs", "let u = \"http://h\"; // run_checks:allow(pii)\n");
+        assert!(allowed(&d, "pii", 2));
+        let d = parse("src/lib.rs", "if c == '\"' { f::<'a>() } // run_checks:allow(pii)\n");
+        assert!(allowed(&d, "pii", 1));
+    }
+}
@@ -0,0 +1,402 @@
+// Snippet
+// File: src/run_checks/test_results.rs
+
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+/// How many failing test names the Details column lists before `+N more`.
+const TOP_FAILURES: usize = 5;
+
+/// Counts from one libtest `test result:` line (one per test binary / doctest run).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TestBinary {
+    pub name: String,
+    /// Crate target the binary was built from (`foo_bar` for `foo_bar-<hash>`).
+    pub target: String,
+    /// Test executable as cargo printed it, relative to the workspace root;
+    /// empty for doctests.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub executable: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub filtered_out: usize,
+}
+
+/// A failing test and the panic message captured from its output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TestFailure {
+    pub binary: String,
+    pub target: String,
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TestSummary {
+    pub binaries: Vec<TestBinary>,
+    pub failures: Vec<TestFailure>,
+}
+
+impl TestSummary {
+    pub fn total(&self) -> TestBinary {
+        let mut t = TestBinary { name: "total".to_string(), ..TestBinary::default() };
+        for b in &self.binaries {
+            t.passed += b.passed;
+            t.failed += b.failed;
+            t.ignored += b.ignored;
+            t.filtered_out += b.filtered_out;
+        }
+        t
+    }
+}
+
+// libtest `--format json` events (nightly / `-Z unstable-options`).
+#[derive(Deserialize)]
+struct JsonEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    stdout: Option<String>,
+    #[serde(default)]
+    passed: usize,
+    #[serde(default)]
+    failed: usize,
+    #[serde(default)]
+    ignored: usize,
+    #[serde(default)]
+    filtered_out: usize,
+}
+
+/// Parse `cargo test` output.
+///
+/// Cargo prints `Running <binary>` / `Doc-tests <crate>` on stderr and libtest
+/// prints each binary's results on stdout, in the same order, so the two are
+/// paired up by position. Both the human format and libtest JSON events are read.
+pub fn parse_libtest(stdout: &str, stderr: &str) -> TestSummary {
+    // Name, target and executable per binary, counts still zero.
+    let mut names = stderr.lines().filter_map(|l| {
+        let l = l.trim();
+        l.strip_prefix("Running ").map(running_binary).or_else(|| {
+            l.strip_prefix("Doc-tests ").map(|c| TestBinary {
+                name: format!("doctests {c}"),
+                target: c.to_string(),
+                ..TestBinary::default()
+            })
+        })
+    });
+
+    let mut summary = TestSummary::default();
+    let mut current: TestBinary = names.next().unwrap_or_default();
+    let mut failed_names: Vec<String> = Vec::new();
+    // Output blocks only describe failures inside a `failures:` section;
+    // `--show-output` also prints blocks for passing tests under `successes:`,
+    // which are skipped.
+    // Current section header: `successes:`, `failures:`, or none.
+    let mut section = "";
+    // (test name, captured lines) for the `---- name stdout ----` block being read.
+    let mut block: Option<(String, Vec<&str>)> = None;
+
+    for line in stdout.lines() {
+        if let Ok(ev) = serde_json::from_str::<JsonEvent>(line) {
+            match (ev.kind.as_str(), ev.event.as_str()) {
+                ("test", "failed") => summary.failures.push(TestFailure {
+                    binary: current.name.clone(),
+                    target: current.target.clone(),
+                    name: ev.name.unwrap_or_default(),
+                    message: panic_message(&ev.stdout.unwrap_or_default()),
+                }),
+                ("suite", "ok" | "failed") => {
+                    summary.binaries.push(TestBinary {
+                        passed: ev.passed,
+                        failed: ev.failed,
+                        ignored: ev.ignored,
+                        filtered_out: ev.filtered_out,
+                        ..std::mem::take(&mut current)
+                    });
+                    current = names.next().unwrap_or_default();
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if line == "failures:" || line == "successes:" {
+            flush_block(&mut block, &current, &mut summary);
+            section = line;
+            continue;
+        }
+        if line.starts_with("test result: ") {
+            flush_block(&mut block, &current, &mut summary);
+        } else if section == "successes:" {
+            continue;
+        } else if let Some(name) =
+            line.strip_prefix("---- ").and_then(|r| r.strip_suffix(" stdout ----"))
+        {
+            flush_block(&mut block, &current, &mut summary);
+            if section == "failures:" {
+                block = Some((name.to_string(), Vec::new()));
+            }
+            continue;
+        } else if let Some((_, lines)) = block.as_mut() {
+            lines.push(line);
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("test ").and_then(|r| r.strip_suffix(" ... FAILED")) {
+            failed_names.push(name.to_string());
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("test result: ") {
+            section = "";
+            // Failures without a captured output block still get listed.
+            for name in failed_names.drain(..) {
+                if !summary.failures.iter().any(|f| f.binary == current.name && f.name == name) {
+                    summary.failures.push(TestFailure {
+                        binary: current.name.clone(),
+                        target: current.target.clone(),
+                        name,
+                        message: String::new(),
+                    });
+                }
+            }
+            let mut b = std::mem::take(&mut current);
+            // "ok. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in ..."
+            let counts = rest.split_once(". ").map(|(_, c)| c).unwrap_or(rest);
+            for part in counts.split(';') {
+                let Some((n, label)) = part.trim().split_once(' ') else {
+                    continue;
+                };
+                let n = n.parse::<usize>().unwrap_or(0);
+                match label {
+                    "passed" => b.passed = n,
+                    "failed" => b.failed = n,
+                    "ignored" => b.ignored = n,
+                    "filtered out" => b.filtered_out = n,
+                    _ => {}
+                }
+            }
+            summary.binaries.push(b);
+            current = names.next().unwrap_or_default();
+        }
+    }
+
+    summary
+}
+
+/// Split `unittests src/lib.rs (target/debug/deps/foo_bar-1a2b)` into the
+/// display name, the executable and the target name taken from it.
+fn running_binary(rest: &str) -> TestBinary {
+    let Some((name, exe)) = rest.split_once(" (") else {
+        return TestBinary { name: rest.to_string(), ..TestBinary::default() };
+    };
+    let exe = exe.trim_end_matches(')');
+    let file = exe.rsplit(['/', '\\']).next().unwrap_or("");
+    let stem = file.strip_suffix(".exe").unwrap_or(file);
+    let target = stem.rsplit_once('-').map(|(t, _)| t).unwrap_or(stem);
+    TestBinary {
+        name: name.to_string(),
+        target: target.to_string(),
+        executable: exe.to_string(),
+        ..TestBinary::default()
+    }
+}
+
+fn flush_block(block: &mut Option<(String, Vec<&str>)>, current: &TestBinary, s: &mut TestSummary) {
+    if let Some((name, lines)) = block.take() {
+        s.failures.push(TestFailure {
+            binary: current.name.clone(),
+            target: current.target.clone(),
+            name,
+            message: panic_message(&lines.join("\n")),
+        });
+    }
+}
+
+/// Pull `<location>: <message>` out of a captured test output block.
+fn panic_message(output: &str) -> String {
+    let mut lines = output.lines().skip_while(|l| !l.contains("panicked at "));
+    let Some(header) = lines.next() else {
+        return output.trim().to_string();
+    };
+    let location = header.split("panicked at ").nth(1).unwrap_or("").trim_end_matches(':');
+    let body: Vec<&str> = lines
+        .take_while(|l| !l.starts_with("note: ") && !l.starts_with("stack backtrace:"))
+        .collect();
+    let body = body.join("\n");
+    let body = body.trim();
+    if body.is_empty() {
+        location.to_string()
+    } else {
+        format!("{location}: {body}")
+    }
+}
+
+/// Details cell: totals across all binaries, then the first few failing tests.
+pub fn summarize(s: &TestSummary) -> String {
+    if s.binaries.is_empty() {
+        return "no test results".to_string();
+    }
+    let t = s.total();
+    let mut out = format!(
+        "{} passed, {} failed, {} ignored, {} filtered",
+        t.passed, t.failed, t.ignored, t.filtered_out
+    );
+    for f in s.failures.iter().take(TOP_FAILURES) {
+        out.push_str(&format!("\nFAILED {}", f.name));
+    }
+    if s.failures.len() > TOP_FAILURES {
+        out.push_str(&format!("\n+{} more", s.failures.len() - TOP_FAILURES));
+    }
+    out
+}
+
+/// Per-binary counts and every failing test with its panic message.
+pub fn render_section(tool: &str, s: &TestSummary) -> String {
+    let mut out = format!(
+        "{}\n{tool} results\n{}\n",
+        "========================================".green(),
+        "========================================".green()
+    );
+    if s.binaries.is_empty() {
+        out.push_str("(no test results)");
+        return out;
+    }
+    let mut lines: Vec<String> = s
+        .binaries
+        .iter()
+        .map(|b| {
+            format!(
+                "{}: {} passed, {} failed, {} ignored, {} filtered",
+                b.name, b.passed, b.failed, b.ignored, b.filtered_out
+            )
+        })
+        .collect();
+    for f in &s.failures {
+        lines.push(format!("FAILED {} ({})", f.name, f.binary));
+        for l in f.message.lines() {
+            lines.push(format!("    {l}"));
+        }
+    }
+    out.push_str(&lines.join("\n"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STDERR: &str = "\
+     Running unittests src/lib.rs (target/debug/deps/demo-1a2b3c)
+   Doc-tests demo
+";
+
+    const HUMAN: &str = "\
+running 2 tests
+test tests::passes ... ok
+test tests::fails ... FAILED
+
+failures:
+
+---- tests::fails stdout ----
+thread 'tests::fails' panicked at src/lib.rs:9:9:
+boom
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::fails
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.10s
+";
+
+    const SHOW_OUTPUT: &str = "\
+running 2 tests
+test tests::fails ... FAILED
+test tests::passes ... ok
+
+successes:
+
+---- tests::passes stdout ----
+hello from passing
+
+
+successes:
+    tests::passes
+
+failures:
+
+---- tests::fails stdout ----
+thread 'tests::fails' panicked at src/lib.rs:9:9:
+boom
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::fails
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+    const JSON: &str = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "tests::passes" }
+{ "type": "test", "event": "ok", "name": "tests::passes", "stdout": "hello from passing\n" }
+{ "type": "test", "event": "failed", "name": "tests::fails", "stdout": "thread 'tests::fails' panicked at src/lib.rs:9:9:\nboom\n" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.001 }
+"#;
+
+    fn only_failure(s: &TestSummary) -> &TestFailure {
+        assert_eq!(s.failures.len(), 1, "{:?}", s.failures);
+        &s.failures[0]
+    }
+
+    #[test]
+    fn human_format() {
+        let s = parse_libtest(HUMAN, STDERR);
+        assert_eq!(s.binaries.len(), 2);
+        assert_eq!(s.binaries[0].name, "unittests src/lib.rs");
+        assert_eq!(s.binaries[0].target, "demo");
+        assert_eq!(s.binaries[0].executable, "target/debug/deps/demo-1a2b3c");
+        assert_eq!((s.binaries[0].passed, s.binaries[0].failed), (1, 1));
+        assert_eq!(s.binaries[1].name, "doctests demo");
+        assert_eq!(s.total().passed, 2);
+        let f = only_failure(&s);
+        assert_eq!(f.name, "tests::fails");
+        assert_eq!(f.binary, "unittests src/lib.rs");
+        assert_eq!(f.message, "src/lib.rs:9:9: boom");
+    }
+
+    #[test]
+    fn show_output_does_not_report_passing_tests() {
+        let s = parse_libtest(SHOW_OUTPUT, STDERR);
+        assert_eq!((s.binaries[0].passed, s.binaries[0].failed), (1, 1));
+        let f = only_failure(&s);
+        assert_eq!(f.name, "tests::fails");
+        assert_eq!(f.message, "src/lib.rs:9:9: boom");
+    }
+
+    #[test]
+    fn json_format() {
+        let s = parse_libtest(JSON, STDERR);
+        assert_eq!(s.binaries.len(), 1);
+        assert_eq!(s.binaries[0].target, "demo");
+        assert_eq!((s.binaries[0].passed, s.binaries[0].failed), (1, 1));
+        let f = only_failure(&s);
+        assert_eq!(f.name, "tests::fails");
+        assert_eq!(f.message, "src/lib.rs:9:9: boom");
+    }
+
+    #[test]
+    fn failure_without_output_block_is_listed() {
+        let out = "test slow ... FAILED\n\ntest result: FAILED. 0 passed; 1 failed; \
+                   0 ignored; 0 measured; 0 filtered out; finished in 0.00s\n";
+        let s = parse_libtest(out, STDERR);
+        assert_eq!(only_failure(&s).name, "slow");
+        assert!(summarize(&s).ends_with("\nFAILED slow"));
+    }
+}
@@ -0,0 +1,373 @@
+// Snippet
+// File: src/run_checks/workspace.rs
+
+use super::run_tools::{unformatted_files, ToolResult, ToolStatus, ToolsRun};
+use super::test_results::TestBinary;
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, Table};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Tools whose output can be attributed to a member; custom tools are opaque.
+const BUILTINS: &[&str] = &["rustfmt", "clippy", "cargo check", "cargo test"];
+
+/// Workspace members as reported by `cargo metadata`.
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub members: Vec<Member>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub name: String,
+    /// Absolute package directory (where its Cargo.toml lives).
+    pub dir: PathBuf,
+    /// Target names with `-` mapped to `_`, as they appear in test binaries,
+    /// with each target's absolute source file.
+    pub targets: Vec<(String, PathBuf)>,
+}
+
+// Subset of `cargo metadata --format-version 1` that we read.
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    targets: Vec<Target>,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+    src_path: PathBuf,
+}
+
+impl Workspace {
+    /// Ask cargo for the workspace around the current directory. `None` when
+    /// cargo is missing or this is not a cargo project.
+    pub fn discover() -> Option<Workspace> {
+        let out = std::process::Command::new("cargo")
+            .args(["metadata", "--no-deps", "--format-version", "1"])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        if !out.status.success() {
+            return None;
+        }
+        let meta: Metadata = serde_json::from_slice(&out.stdout).ok()?;
+        let members = meta
+            .packages
+            .into_iter()
+            .filter(|p| meta.workspace_members.contains(&p.id))
+            .map(|p| Member {
+                name: p.name,
+                dir: p.manifest_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                targets: p
+                    .targets
+                    .into_iter()
+                    .map(|t| (t.name.replace('-', "_"), t.src_path))
+                    .collect(),
+            })
+            .collect();
+        Some(Workspace { root: meta.workspace_root, members })
+    }
+
+    /// More than one member: worth running with `--workspace` and a matrix.
+    pub fn is_multi(&self) -> bool {
+        self.members.len() > 1
+    }
+
+    /// The member whose directory is the deepest prefix of `path` (absolute).
+    fn member_for_path(&self, path: &Path) -> Option<&Member> {
+        self.members
+            .iter()
+            .filter(|m| path.starts_with(&m.dir))
+            .max_by_key(|m| m.dir.components().count())
+    }
+
+    /// The member a test binary was built from. Several members can have a
+    /// target of the same name (`tests/integration.rs`): the binary's source
+    /// path relative to its package narrows them down, then the sources its
+    /// dep-info file lists. Doctests only carry a crate name.
+    fn owner_of(&self, b: &TestBinary) -> Owner<'_> {
+        let mut fits: Vec<&Member> =
+            self.members.iter().filter(|m| m.src_of(&b.target).next().is_some()).collect();
+        if fits.len() > 1 {
+            let src = Path::new(b.name.strip_prefix("unittests ").unwrap_or(&b.name));
+            fits.retain(|m| m.src_of(&b.target).any(|p| p == m.dir.join(src)));
+        }
+        if fits.len() > 1 {
+            if let Some(deps) = self.dep_info(&b.executable) {
+                let listed = |p: &Path| {
+                    let rel = p.strip_prefix(&self.root).unwrap_or(p);
+                    deps.iter().any(|d| Path::new(d) == p || Path::new(d) == rel)
+                };
+                fits.retain(|m| m.src_of(&b.target).any(listed));
+            }
+        }
+        match fits.len() {
+            0 => Owner::Unknown,
+            1 => Owner::One(fits[0]),
+            _ => Owner::Ambiguous(fits),
+        }
+    }
+
+    /// Source files of a test executable, from the `.d` file cargo writes
+    /// next to it; `None` if there is none.
+    fn dep_info(&self, executable: &str) -> Option<Vec<String>> {
+        if executable.is_empty() {
+            return None;
+        }
+        let exe = self.root.join(executable);
+        let text = std::fs::read_to_string(exe.with_extension("d")).ok()?;
+        let (_, deps) = text.lines().next()?.split_once(": ")?;
+        Some(deps.split_whitespace().map(str::to_string).collect())
+    }
+}
+
+impl Member {
+    /// Source files of this member's targets named `target`.
+    fn src_of<'a>(&'a self, target: &'a str) -> impl Iterator<Item = &'a Path> + 'a {
+        self.targets.iter().filter(move |(t, _)| t == target).map(|(_, p)| p.as_path())
+    }
+}
+
+/// Which member a test binary belongs to.
+enum Owner<'a> {
+    One(&'a Member),
+    /// Equally plausible members; the binary is credited to none of them.
+    Ambiguous(Vec<&'a Member>),
+    Unknown,
+}
+
+/// One member's row of the results matrix.
+#[derive(Clone, Debug, Serialize)]
+pub struct MemberResult {
+    pub name: String,
+    pub path: String,
+    pub cells: Vec<MemberCell>,
+}
+
+/// What one tool reported about one member.
+#[derive(Clone, Debug, Serialize)]
+pub struct MemberCell {
+    pub tool: String,
+    pub status: ToolStatus,
+    pub details: String,
+}
+
+/// Attribute tool output back to workspace members: diagnostics and
+/// unformatted files by path, test binaries by target name and source path.
+pub fn member_results(ws: &Workspace, run: &ToolsRun) -> Vec<MemberResult> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    ws.members
+        .iter()
+        .map(|m| MemberResult {
+            name: m.name.clone(),
+            path: m
+                .dir
+                .strip_prefix(&ws.root)
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| m.dir.display().to_string()),
+            cells: run
+                .results
+                .iter()
+                .filter(|r| BUILTINS.contains(&r.name.as_str()))
+                .map(|r| member_cell(ws, &cwd, m, r))
+                .collect(),
+        })
+        .collect()
+}
+
+fn member_cell(ws: &Workspace, cwd: &Path, m: &Member, r: &ToolResult) -> MemberCell {
+    let cell = |status, details: String| MemberCell { tool: r.name.clone(), status, details };
+    match r.status {
+        ToolStatus::Skipped | ToolStatus::Cancelled => {
+            return cell(ToolStatus::Skipped, "not run".to_string())
+        }
+        ToolStatus::TimedOut => return cell(ToolStatus::TimedOut, "timed out".to_string()),
+        _ => {}
+    }
+    let mine = |p: &Path| ws.member_for_path(p).is_some_and(|o| o.name == m.name);
+
+    if let Some(diags) = &r.diagnostics {
+        // Compiler paths are relative to the workspace root.
+        let (mut errors, mut warnings) = (0, 0);
+        for d in diags.iter().filter(|d| mine(&ws.root.join(&d.file))) {
+            match d.level.as_str() {
+                "error" => errors += 1,
+                "warning" => warnings += 1,
+                _ => {}
+            }
+        }
+        return match (errors, warnings) {
+            (0, 0) => cell(ToolStatus::Success, "ok".to_string()),
+            (0, w) => cell(ToolStatus::Success, format!("warning: {w}")),
+            (e, 0) => cell(ToolStatus::Failed, format!("error: {e}")),
+            (e, w) => cell(ToolStatus::Failed, format!("error: {e}, warning: {w}")),
+        };
+    }
+    if let Some(summary) = &r.tests {
+        let (mut bins, mut ambiguous) = (Vec::new(), 0);
+        for b in &summary.binaries {
+            match ws.owner_of(b) {
+                Owner::One(o) if o.name == m.name => bins.push(b),
+                Owner::Ambiguous(os) if os.iter().any(|o| o.name == m.name) => ambiguous += 1,
+                _ => {}
+            }
+        }
+        let passed: usize = bins.iter().map(|b| b.passed).sum();
+        let failed: usize = bins.iter().map(|b| b.failed).sum();
+        // Binaries that several members could own are counted for none.
+        let note = match ambiguous {
+            0 => String::new(),
+            n => format!(" (+{n} ambiguous)"),
+        };
+        return match (bins.is_empty(), failed) {
+            (true, _) if ambiguous > 0 => {
+                cell(ToolStatus::Skipped, format!("{ambiguous} ambiguous test binaries"))
+            }
+            // Its tests never ran: a build error, or an earlier binary aborted the run.
+            (true, _) if !r.ok() => cell(ToolStatus::Skipped, "not run".to_string()),
+            (true, _) => cell(ToolStatus::Success, "no tests".to_string()),
+            (false, 0) => cell(ToolStatus::Success, format!("{passed} passed{note}")),
+            (false, f) => cell(ToolStatus::Failed, format!("{passed} passed, {f} failed{note}")),
+        };
+    }
+    // rustfmt: paths are relative to the current directory.
+    let files = unformatted_files(&r.stdout);
+    let n = files.iter().filter(|f| mine(&cwd.join(f))).count();
+    match n {
+        0 => cell(ToolStatus::Success, "ok".to_string()),
+        n => cell(ToolStatus::Failed, format!("{n} file(s) unformatted")),
+    }
+}
+
+/// Render members × tools, one colored cell per tool.
+pub fn render_member_matrix(members: &[MemberResult]) -> String {
+    let mut t = Table::new();
+    let mut header = vec!["Crate".to_string(), "Path".to_string()];
+    if let Some(first) = members.first() {
+        header.extend(first.cells.iter().map(|c| c.tool.clone()));
+    }
+    t.load_preset(UTF8_FULL).set_header(header);
+    for m in members {
+        let mut row = vec![Cell::new(&m.name).add_attribute(Attribute::Bold), Cell::new(&m.path)];
+        for c in &m.cells {
+            let color = match c.status {
+                ToolStatus::Success if c.details.starts_with("warning") => Color::Yellow,
+                ToolStatus::Success => Color::Green,
+                ToolStatus::Failed => Color::Red,
+                ToolStatus::TimedOut => Color::Magenta,
+                ToolStatus::Skipped | ToolStatus::Cancelled => Color::Yellow,
+            };
+            row.push(Cell::new(&c.details).fg(color));
+        }
+        t.add_row(row);
+    }
+    t.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_checks::test_results::TestSummary;
+
+    fn member(name: &str, dir: &str, targets: &[(&str, &str)]) -> Member {
+        Member {
+            name: name.to_string(),
+            dir: PathBuf::from(dir),
+            targets: targets
+                .iter()
+                .map(|(t, src)| (t.to_string(), Path::new(dir).join(src)))
+                .collect(),
+        }
+    }
+
+    fn binary(name: &str, target: &str) -> TestBinary {
+        TestBinary { name: name.to_string(), target: target.to_string(), ..TestBinary::default() }
+    }
+
+    fn workspace() -> Workspace {
+        Workspace {
+            root: PathBuf::from("/ws"),
+            members: vec![
+                member(
+                    "alpha",
+                    "/ws/alpha",
+                    &[("alpha", "src/lib.rs"), ("integration", "tests/integration.rs")],
+                ),
+                member(
+                    "beta",
+                    "/ws/beta",
+                    &[("beta", "src/lib.rs"), ("integration", "tests/it/integration.rs")],
+                ),
+            ],
+        }
+    }
+
+    fn owner(b: &TestBinary) -> Option<String> {
+        match workspace().owner_of(b) {
+            Owner::One(m) => Some(m.name.clone()),
+            Owner::Ambiguous(_) => Some("ambiguous".to_string()),
+            Owner::Unknown => None,
+        }
+    }
+
+    #[test]
+    fn unique_target_name_picks_its_member() {
+        assert_eq!(owner(&binary("unittests src/lib.rs", "beta")).as_deref(), Some("beta"));
+        assert_eq!(owner(&binary("doctests alpha", "alpha")).as_deref(), Some("alpha"));
+    }
+
+    #[test]
+    fn shared_target_name_is_resolved_by_source_path() {
+        let it = |src: &str| owner(&binary(src, "integration"));
+        assert_eq!(it("tests/integration.rs").as_deref(), Some("alpha"));
+        assert_eq!(it("tests/it/integration.rs").as_deref(), Some("beta"));
+        assert_eq!(it("tests/elsewhere.rs"), None);
+    }
+
+    #[test]
+    fn same_source_path_without_dep_info_is_ambiguous() {
+        let mut ws = workspace();
+        ws.members[1] = member("beta", "/ws/beta", &[("integration", "tests/integration.rs")]);
+        let b = TestBinary {
+            executable: "target/debug/deps/integration-0123".to_string(),
+            ..binary("tests/integration.rs", "integration")
+        };
+        assert!(matches!(ws.owner_of(&b), Owner::Ambiguous(ref ms) if ms.len() == 2));
+    }
+
+    #[test]
+    fn members_without_test_results_are_not_run() {
+        let ws = workspace();
+        let failing = TestBinary { failed: 1, ..binary("unittests src/lib.rs", "alpha") };
+        let r = ToolResult {
+            name: "cargo test".to_string(),
+            command: Vec::new(),
+            status: ToolStatus::Failed,
+            allow_failure: false,
+            details: String::new(),
+            elapsed_secs: 0.0,
+            waited_secs: 0.0,
+            lock_wait_secs: 0.0,
+            stdout: String::new(),
+            stderr: String::new(),
+            diagnostics: None,
+            tests: Some(TestSummary { binaries: vec![failing], failures: Vec::new() }),
+        };
+        let cell = |m: usize| member_cell(&ws, Path::new("/ws"), &ws.members[m], &r);
+        assert_eq!(cell(0).status, ToolStatus::Failed);
+        assert_eq!(cell(1).status, ToolStatus::Skipped);
+        assert_eq!(cell(1).details, "not run");
+    }
+}
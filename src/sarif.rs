@@ -0,0 +1,131 @@
+// Package run_checks
+// File: src/sarif.rs
+
+use crate::run_checks::{ChecksReport, Severity, RULES};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Write a SARIF 2.1.0 log with one result per privacy/secret finding.
+/// Findings silenced by `run_checks:allow(...)` carry an in-source suppression;
+/// with a baseline, each result is marked `new` or `unchanged`.
+pub fn write_sarif(path: &Path, report: &ChecksReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let text = serde_json::to_string_pretty(&render_sarif(report))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, text)
+}
+
+fn render_sarif(report: &ChecksReport) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|r| {
+            json!({
+                "id": r.id,
+                "name": r.id,
+                "shortDescription": { "text": r.description },
+                "defaultConfiguration": { "level": level(r.severity) },
+                "properties": { "version": r.version, "severity": r.severity.as_str() },
+            })
+        })
+        .collect();
+
+    let mut results: Vec<Value> = Vec::new();
+    for row in &report.privacy {
+        let groups = [
+            (&row.findings, Aside::No),
+            (&row.suppressed, Aside::Suppressed),
+            (&row.baselined, Aside::Baselined),
+        ];
+        for (f, aside) in groups.into_iter().flat_map(|(fs, a)| fs.iter().map(move |f| (f, a))) {
+            let rule_index = RULES.iter().position(|r| r.id == f.rule);
+            let rule = rule_index.map(|i| &RULES[i]);
+            let desc = rule.map_or("Privacy/security finding", |r| r.description);
+            // Rules added through `[scan].patterns` have no catalog entry.
+            let severity = f.severity.unwrap_or(Severity::Medium);
+            let mut physical = json!({
+                "artifactLocation": {
+                    "uri": f.path.trim_start_matches("./"),
+                    "uriBaseId": "%SRCROOT%",
+                },
+            });
+            if let (Some(line), Some(col), Some(end)) = (f.line, f.column, f.end_column) {
+                physical["region"] = json!({
+                    "startLine": line,
+                    "startColumn": col,
+                    "endColumn": end,
+                });
+            }
+            // Candidate rows name the matched value; secret rows must not echo it.
+            let text = match (f.rule.as_str(), &f.detail) {
+                ("username" | "hostname" | "ip-address", _) => format!("{desc}: {}", row.value),
+                (_, Some(detail)) => format!("{desc}: {detail}"),
+                _ => desc.to_string(),
+            };
+            let mut result = json!({
+                "ruleId": f.rule,
+                "level": level(severity),
+                "message": { "text": text },
+                "locations": [{ "physicalLocation": physical }],
+            });
+            if let Some(i) = rule_index {
+                result["ruleIndex"] = json!(i);
+            }
+            if !f.fingerprint.is_empty() {
+                result["partialFingerprints"] = json!({ "run_checks/v1": f.fingerprint });
+            }
+            match aside {
+                Aside::Suppressed => result["suppressions"] = json!([{ "kind": "inSource" }]),
+                Aside::Baselined => result["baselineState"] = json!("unchanged"),
+                Aside::No if report.baseline_loaded => result["baselineState"] = json!("new"),
+                Aside::No => {}
+            }
+            results.push(result);
+        }
+    }
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                "rules": rules,
+            },
+        },
+        "columnKind": "unicodeCodePoints",
+        "results": results,
+    });
+    if let Ok(cwd) = std::env::current_dir() {
+        let mut uri = format!("file://{}", cwd.display());
+        if !uri.ends_with('/') {
+            uri.push('/');
+        }
+        run["originalUriBaseIds"] = json!({ "%SRCROOT%": { "uri": uri } });
+    }
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [run],
+    })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// Why a finding is not counted as found, if it is not.
+#[derive(Clone, Copy)]
+enum Aside {
+    No,
+    Suppressed,
+    Baselined,
+}
@@ -1,63 +1,100 @@
 // Package run_checks
 // File: src/tree.rs
 
+use crate::config::Config;
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use std::{fs, path::Path};
 
-const ALLOWED_EXTS: &[&str] = &["rs", "md", "sh", "toml"];
+/// Whether a tree node is a directory or an allowed file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Dir,
+    File,
+}
+
+/// One entry of the directory tree, in walk order; `depth` 0 is directly under `.`.
+#[derive(Clone, Debug, Serialize)]
+pub struct TreeNode {
+    pub path: String,
+    pub kind: NodeKind,
+    pub depth: usize,
+}
 
-fn should_skip_dir(p: &Path) -> bool {
-    let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-    name == ".git" || name == "target" || name == "node_modules"
+/// A directory walk; `error` is set if the walk stopped early, with `nodes`
+/// holding everything visited before that.
+#[derive(Clone, Debug, Serialize)]
+pub struct Tree {
+    pub max_depth: usize,
+    pub all_files: bool,
+    pub nodes: Vec<TreeNode>,
+    pub error: Option<String>,
 }
 
-fn is_allowed_file(p: &Path) -> bool {
-    p.extension().and_then(|s| s.to_str()).map(|ext| ALLOWED_EXTS.contains(&ext)).unwrap_or(false)
+/// Walk `.` to `max_depth`, keeping only allowed files (unless `[tree].all_files`)
+/// and skipping `[files].skip_dirs`.
+pub fn collect_tree(cfg: &Config, max_depth: usize) -> Tree {
+    let mut nodes = Vec::new();
+    let error =
+        visit_dirs(cfg, Path::new("."), 0, max_depth, &mut nodes).err().map(|e| e.to_string());
+    Tree { max_depth, all_files: cfg.tree.all_files, nodes, error }
 }
 
-/// Build a directory tree string from `.` to `max_depth`, showing only allowed files
-/// and skipping `target/`, `.git/`, `node_modules/`.
-pub fn collect_tree(max_depth: usize) -> String {
+/// Build a directory tree string from a collected walk.
+pub fn render_tree(tree: &Tree) -> String {
     let mut out = String::new();
+    let which = if tree.all_files { "all files" } else { "allowed files only" };
     out.push_str(&format!(
         "{}\n",
-        format!("Directory structure (allowed files only, up to {max_depth} levels):").cyan()
+        format!("Directory structure ({which}, up to {} levels):", tree.max_depth).cyan()
     ));
-    if let Err(e) = visit_dirs(Path::new("."), 0, max_depth, &mut out) {
+    for n in &tree.nodes {
+        let tag = match n.kind {
+            NodeKind::Dir => "[DIR] ",
+            NodeKind::File => "[FILE]",
+        };
+        out.push_str(&format!("{:indent$}{tag} {}\n", "", n.path, indent = n.depth * 2));
+    }
+    if let Some(e) = &tree.error {
         out.push_str(&format!("Error: {e}\n"));
     }
     out
 }
 
-fn visit_dirs(dir: &Path, level: usize, max_depth: usize, out: &mut String) -> std::io::Result<()> {
+fn visit_dirs(
+    cfg: &Config,
+    dir: &Path,
+    level: usize,
+    max_depth: usize,
+    out: &mut Vec<TreeNode>,
+) -> std::io::Result<()> {
     if level > max_depth {
         return Ok(());
     }
     if dir.is_dir() {
-        if should_skip_dir(dir) && level > 0 {
+        if cfg.files.is_skipped_dir(dir) && level > 0 {
             return Ok(());
         }
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() {
-                if should_skip_dir(&path) {
+                if cfg.files.is_skipped_dir(&path) {
                     continue;
                 }
-                out.push_str(&format!(
-                    "{:indent$}[DIR]  {}\n",
-                    "",
-                    path.display(),
-                    indent = level * 2
-                ));
-                visit_dirs(&path, level + 1, max_depth, out)?;
-            } else if is_allowed_file(&path) {
-                out.push_str(&format!(
-                    "{:indent$}[FILE] {}\n",
-                    "",
-                    path.display(),
-                    indent = level * 2
-                ));
+                out.push(TreeNode {
+                    path: path.display().to_string(),
+                    kind: NodeKind::Dir,
+                    depth: level,
+                });
+                visit_dirs(cfg, &path, level + 1, max_depth, out)?;
+            } else if cfg.tree.all_files || cfg.files.is_allowed_file(&path) {
+                out.push(TreeNode {
+                    path: path.display().to_string(),
+                    kind: NodeKind::File,
+                    depth: level,
+                });
             }
         }
     }
@@ -78,25 +78,24 @@ fn pipe_to_with_args(cmd: &str, args: &[&str], text: &str) -> bool {
     child.wait().map(|s| s.success()).unwrap_or(false)
 }
 
-/// Remove ANSI SGR escape sequences.
+/// Remove ANSI SGR escape sequences (and the `ESC ( B` charset resets some
+/// terminal libraries emit alongside them).
 pub fn strip_ansi_sgr(s: &str) -> String {
-    let bytes = s.as_bytes();
     let mut out = String::with_capacity(s.len());
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i] == 0x1b && i + 1 < bytes.len() && bytes[i + 1] == b'[' {
-            i += 2;
-            while i < bytes.len() {
-                let b = bytes[i];
-                if (b'@'..=b'~').contains(&b) {
-                    i += 1;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            for b in chars.by_ref() {
+                if ('@'..='~').contains(&b) {
                     break;
                 }
-                i += 1;
             }
+        } else if c == '\x1b' && chars.peek() == Some(&'(') {
+            chars.next();
+            chars.next();
         } else {
-            out.push(bytes[i] as char);
-            i += 1;
+            out.push(c);
         }
     }
     out
@@ -0,0 +1,311 @@
+// Package run_checks
+// File: src/watch.rs
+
+use crate::config::{self, Config, FilesConfig};
+use crate::report::{JsonReport, OutputFormat};
+use crate::run_checks::{self, ChecksReport, FmtMode, Selection};
+use crate::util;
+use globset::GlobSet;
+use notify::{EventKind, RecursiveMode, Watcher};
+use owo_colors::OwoColorize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+
+/// How much of the pipeline a change calls for. Ordered so merging keeps the larger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Scope {
+    /// Docs, scripts, config and leak-prone files: only the privacy/security scans.
+    Privacy,
+    /// Rust sources or manifests: tools, matrices and scans.
+    Full,
+    /// `run_checks.toml`: reload the configuration, then a full run.
+    Config,
+}
+
+/// Changed paths collected since the last run, and the scope they require.
+struct Change {
+    scope: Scope,
+    paths: Vec<String>,
+}
+
+impl Change {
+    fn merge(&mut self, scope: Scope, path: String) {
+        self.scope = self.scope.max(scope);
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.paths.len() {
+            0 => "startup".to_string(),
+            1..=3 => self.paths.join(", "),
+            n => format!("{} and {} more", self.paths[..3].join(", "), n - 3),
+        }
+    }
+}
+
+/// Options for `run_checks watch`.
+pub struct WatchOptions {
+    pub run_extras: bool,
+    pub fmt_mode: FmtMode,
+    pub debounce: Duration,
+    pub clear: bool,
+    pub format: OutputFormat,
+}
+
+/// Run checks once, then again after every (debounced) change to the
+/// project. A change arriving mid-run cancels the run and starts over; a
+/// change to the config file (`cfg_path`) reloads it through `reload` first.
+/// Only returns if the watcher cannot be set up or stops.
+pub async fn watch(
+    mut cfg: Config,
+    cfg_path: Option<PathBuf>,
+    selection: &Selection,
+    opts: WatchOptions,
+    reload: impl Fn() -> Result<Config, String>,
+) -> Result<(), String> {
+    let (tx, rx) = mpsc::unbounded_channel::<PathBuf>();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(ev) = res {
+            if !matches!(ev.kind, EventKind::Access(_)) {
+                for p in ev.paths {
+                    let _ = tx.send(p);
+                }
+            }
+        }
+    })
+    .map_err(|e| format!("failed to start file watcher: {e}"))?;
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let cfg_path = cfg_path.map(|p| cwd.join(p));
+    let mut filter = Classifier::new(&cfg, &cwd, cfg_path.clone())?;
+    let mut tree = Tree { watcher, rx, watched: HashSet::new() };
+    tree.watch_dirs(&cwd, &filter)?;
+    // A config file found above the project directory gets its own watch.
+    if let Some(dir) = cfg_path.as_deref().and_then(Path::parent).filter(|d| !d.starts_with(&cwd)) {
+        tree.watcher.watch(dir, RecursiveMode::NonRecursive).map_err(|e| watch_err(dir, e))?;
+    }
+
+    // `auto` would mean write mode in a terminal, and rewriting files retriggers the watch.
+    let fmt_mode = if opts.fmt_mode == FmtMode::Auto { FmtMode::Check } else { opts.fmt_mode };
+
+    let mut last: Option<ChecksReport> = None;
+    let mut change = Change { scope: Scope::Full, paths: Vec::new() };
+    loop {
+        if change.scope == Scope::Config {
+            match reload().and_then(|new| {
+                selection.validate(&new)?;
+                let f = Classifier::new(&new, &cwd, cfg_path.clone())?;
+                Ok((new, f))
+            }) {
+                Ok((new, f)) => {
+                    (cfg, filter) = (new, f);
+                    // `[files].skip_dirs` may have changed: watch what it no longer skips.
+                    if let Err(e) = tree.watch_dirs(&cwd, &filter) {
+                        eprintln!("{}", format!("[watch] {e}").yellow());
+                    }
+                }
+                Err(e) => eprintln!(
+                    "{}",
+                    format!("[watch] {e}; keeping the previous configuration").yellow()
+                ),
+            }
+        }
+        let started = std::time::Instant::now();
+        let report = match (change.scope, last.take()) {
+            (Scope::Privacy, Some(mut prev)) => {
+                let scan = run_checks::collect_privacy_rows(&cfg, opts.run_extras, selection);
+                prev.ok = prev.tools_ok() && scan.ok(opts.run_extras);
+                prev.privacy = scan.rows;
+                prev.baseline_loaded = scan.baseline_loaded;
+                prev
+            }
+            _ => {
+                let (cancel_tx, cancel_rx) = watch::channel(false);
+                let run = run_checks::run_checks_cancellable(
+                    &cfg,
+                    opts.run_extras,
+                    fmt_mode,
+                    selection,
+                    cancel_rx,
+                );
+                tokio::pin!(run);
+                let mut next: Option<Change> = None;
+                let report = loop {
+                    tokio::select! {
+                        report = &mut run => break report,
+                        Some(p) = tree.recv(&filter) => {
+                            if let Some((scope, path)) = filter.classify(&p) {
+                                next.get_or_insert(Change { scope: Scope::Full, paths: Vec::new() })
+                                    .merge(scope, path);
+                                let _ = cancel_tx.send(true);
+                            }
+                        }
+                    }
+                };
+                if let Some(n) = next {
+                    // The run was cancelled; start over with everything it missed.
+                    eprintln!(
+                        "{}",
+                        format!("[watch] changed: {}; restarting", n.describe()).yellow()
+                    );
+                    change = debounce(&filter, &mut tree, opts.debounce, n).await;
+                    change.scope = change.scope.max(Scope::Full);
+                    continue;
+                }
+                report
+            }
+        };
+
+        print_report(&report, &opts);
+        eprintln!(
+            "{}",
+            format!(
+                "[watch] {} run after {} took {:.1}s; waiting for changes...",
+                if change.scope == Scope::Privacy { "privacy-only" } else { "full" },
+                change.describe(),
+                started.elapsed().as_secs_f64()
+            )
+            .cyan()
+        );
+        last = Some(report);
+
+        let first = loop {
+            let Some(p) = tree.recv(&filter).await else {
+                return Err("file watcher stopped".to_string());
+            };
+            if let Some((scope, path)) = filter.classify(&p) {
+                break Change { scope, paths: vec![path] };
+            }
+        };
+        change = debounce(&filter, &mut tree, opts.debounce, first).await;
+    }
+}
+
+fn watch_err(p: &Path, e: notify::Error) -> String {
+    format!("failed to watch {}: {e}", p.display())
+}
+
+/// One non-recursive watch per project directory outside `[files].skip_dirs`,
+/// so `target/` and `.git/` never produce events, and the paths they report.
+struct Tree<W> {
+    watcher: W,
+    rx: mpsc::UnboundedReceiver<PathBuf>,
+    watched: HashSet<PathBuf>,
+}
+
+impl<W: Watcher> Tree<W> {
+    /// Watch `root` and every directory below it that is not skipped or
+    /// already watched.
+    fn watch_dirs(&mut self, root: &Path, filter: &Classifier) -> Result<(), String> {
+        for entry in filter.files.walk(root).filter(|e| e.file_type().is_dir()) {
+            let dir = entry.path();
+            if self.watched.contains(dir) {
+                continue;
+            }
+            self.watcher.watch(dir, RecursiveMode::NonRecursive).map_err(|e| watch_err(dir, e))?;
+            self.watched.insert(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// The next changed path. A directory created in the project is watched
+    /// (with its subdirectories) before it is returned; a removed one is
+    /// forgotten, so it is watched again if it comes back.
+    async fn recv(&mut self, filter: &Classifier) -> Option<PathBuf> {
+        let p = self.rx.recv().await?;
+        if !p.exists() {
+            self.watched.retain(|d| !d.starts_with(&p));
+        } else if p.is_dir() && !self.watched.contains(&p) && filter.project_path(&p).is_some() {
+            if let Err(e) = self.watch_dirs(&p, filter) {
+                eprintln!("{}", format!("[watch] {e}").yellow());
+            }
+        }
+        Some(p)
+    }
+}
+
+/// Keep collecting changes until none arrive for `quiet`.
+async fn debounce<W: Watcher>(
+    filter: &Classifier,
+    tree: &mut Tree<W>,
+    quiet: Duration,
+    mut change: Change,
+) -> Change {
+    while let Ok(Some(p)) = tokio::time::timeout(quiet, tree.recv(filter)).await {
+        if let Some((scope, path)) = filter.classify(&p) {
+            change.merge(scope, path);
+        }
+    }
+    change
+}
+
+/// What of the current configuration decides whether a changed path matters.
+struct Classifier {
+    files: FilesConfig,
+    leak_names: GlobSet,
+    cwd: PathBuf,
+    /// The loaded config file, absolute; `None` when running on defaults.
+    cfg_path: Option<PathBuf>,
+}
+
+impl Classifier {
+    fn new(cfg: &Config, cwd: &Path, cfg_path: Option<PathBuf>) -> Result<Classifier, String> {
+        let (leak_names, _) = cfg.scan.leak_file_globs()?;
+        Ok(Classifier { files: cfg.files.clone(), leak_names, cwd: cwd.to_path_buf(), cfg_path })
+    }
+
+    /// `path` relative to the project, or `None` if it lies outside the
+    /// project or under a skipped directory.
+    fn project_path<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        let rel = match path.strip_prefix(&self.cwd) {
+            Ok(rel) => rel,
+            // Only the config file is watched outside the project.
+            Err(_) if path.is_absolute() => return None,
+            Err(_) => path,
+        };
+        let rel = rel.strip_prefix(".").unwrap_or(rel);
+        match rel.ancestors().any(|a| a.file_name().is_some() && self.files.is_skipped_dir(a)) {
+            true => None,
+            false => Some(rel),
+        }
+    }
+
+    /// Decide what a changed path calls for, or `None` if it does not matter:
+    /// anything under a skipped directory, and files neither scanned nor built.
+    fn classify(&self, path: &Path) -> Option<(Scope, String)> {
+        if self.cfg_path.as_deref() == Some(path) {
+            return Some((Scope::Config, path.display().to_string()));
+        }
+        let rel = self.project_path(path)?;
+        let name = rel.file_name()?.to_str()?;
+        let shown = rel.display().to_string();
+        if name == config::CONFIG_FILE {
+            return Some((Scope::Config, shown));
+        }
+        if name == "Cargo.toml"
+            || name == "Cargo.lock"
+            || rel.extension().is_some_and(|e| e == "rs")
+        {
+            return Some((Scope::Full, shown));
+        }
+        if self.files.is_allowed_file(rel) || self.leak_names.is_match(name) {
+            return Some((Scope::Privacy, shown));
+        }
+        None
+    }
+}
+
+fn print_report(report: &ChecksReport, opts: &WatchOptions) {
+    match opts.format {
+        OutputFormat::Json => {
+            JsonReport { ok: report.ok, checks: Some(report), ..JsonReport::new("watch") }.print()
+        }
+        OutputFormat::Text => {
+            util::maybe_clear(opts.clear);
+            println!("{}", report.render());
+        }
+    }
+}